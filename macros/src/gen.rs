@@ -0,0 +1,1313 @@
+use crate::parse;
+use crate::parse::{MacroArgs, SysArgs, SystemArgs};
+use inflector::*;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{parse_quote, Error, GenericParam, ItemFn, Meta, Signature};
+
+pub fn commandify(
+    args: Punctuated<Meta, syn::Token![,]>,
+    item: ItemFn,
+    entity_command: bool,
+) -> Result<TokenStream, Error> {
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = item;
+    let Signature {
+        constness,
+        asyncness,
+        unsafety,
+        abi,
+        fn_token,
+        ident,
+        generics,
+        inputs,
+        variadic,
+        output,
+        ..
+    } = sig;
+
+    // I actually have no idea if we should care about this case
+    if let Some(variadic) = variadic {
+        return Err(Error::new(variadic.span(), "command cannot be variadic"));
+    }
+
+    // parse return argument
+    let do_return = parse::return_type(&output)?;
+
+    // parse macro args
+    let MacroArgs {
+        no_trait,
+        no_world,
+        name,
+        struct_name,
+        trait_name,
+        ecs_root,
+        serde,
+        map_entities,
+        console,
+        schema,
+        debug,
+        clone,
+        event,
+        system_adapter,
+        delay,
+        run_if,
+        on_skip,
+        requires,
+        without,
+        unique,
+        once,
+        merge,
+        priority,
+        after,
+        key,
+        undo,
+        replicate,
+        id,
+        version,
+        migrations,
+    } = parse::macro_args(&args, ident.clone())?;
+
+    // generate default names late so that the `name` field applies
+    let command_struct = if entity_command {
+        "EntityCommand"
+    } else {
+        "Command"
+    };
+    let struct_name = struct_name.unwrap_or_else(|| {
+        Ident::new(
+            &format!("{}{command_struct}", name.to_string().to_pascal_case()),
+            name.span(),
+        )
+    });
+    let trait_name = trait_name.unwrap_or_else(|| {
+        Ident::new(
+            &format!("{command_struct}s{}Ext", name.to_string().to_pascal_case()),
+            name.span(),
+        )
+    });
+    let ecs_root = ecs_root.unwrap_or_else(|| parse_quote!(::bevy::ecs));
+
+    // parse generics
+    let mut generic_names = Vec::<TokenStream>::new();
+    for param in &generics.params {
+        let name = match param {
+            GenericParam::Lifetime(inner) => {
+                let token = &inner.lifetime;
+                quote!(#token)
+            }
+            GenericParam::Type(inner) => {
+                let token = &inner.ident;
+                quote!(#token)
+            }
+            GenericParam::Const(inner) => {
+                let token = &inner.ident;
+                quote!(#token)
+            }
+        };
+        generic_names.push(name);
+    }
+
+    // parse doc comments
+    let docs = parse::docs(&attrs);
+
+    // parse fn args
+    let SysArgs {
+        entity,
+        entity_ident,
+        fields,
+        field_types,
+        field_attrs,
+        field_docs,
+        def_field_names,
+        impl_field_names,
+        args,
+    } = parse::fn_args(&inputs, entity_command)?;
+
+    // field attributes only make sense on the generated struct, not the original fn
+    let mut inputs = inputs;
+    parse::strip_field_attrs(&mut inputs);
+
+    if !serde && field_attrs.iter().any(|attrs| !attrs.is_empty()) {
+        return Err(Error::new(
+            inputs.span(),
+            "`#[serde]` parameter attributes require `#[command(serde)]`",
+        ));
+    }
+    if schema && !serde {
+        return Err(Error::new(
+            Span::call_site(),
+            "`schema` requires `#[command(serde)]`",
+        ));
+    }
+    if let (false, Some((_, path))) = (serde, migrations.first()) {
+        return Err(Error::new(
+            path.span(),
+            "`migrate_from` requires `#[command(serde)]`",
+        ));
+    }
+
+    // commands are versioned after their newest migration unless told otherwise
+    let version = version.unwrap_or_else(|| {
+        migrations
+            .iter()
+            .map(|(from, _)| from + 1)
+            .max()
+            .unwrap_or(1)
+    });
+    if let Some((from, path)) = migrations.iter().find(|(from, _)| *from >= version) {
+        return Err(Error::new(
+            path.span(),
+            format!("cannot migrate from v{from}, which is not older than the current version v{version}"),
+        ));
+    }
+    if let Some(id) = id.filter(|&id| id > parse::MAX_ID) {
+        return Err(Error::new(
+            Span::call_site(),
+            format!(
+                "`id` must be at most {}, so that it is exact in JSON, found {id}",
+                parse::MAX_ID
+            ),
+        ));
+    }
+    let id = id.unwrap_or_else(|| parse::stable_hash(&name.to_string()));
+
+    if replicate && !serde {
+        return Err(Error::new(
+            Span::call_site(),
+            "`replicate` requires `#[command(serde)]`",
+        ));
+    }
+    let has_filter = !requires.is_empty() || !without.is_empty();
+    if let (None, false, Some(on_skip)) = (&run_if, has_filter, &on_skip) {
+        return Err(Error::new(
+            on_skip.span(),
+            "`on_skip` requires `run_if`, `requires` or `without`",
+        ));
+    }
+    if has_filter && !entity_command {
+        return Err(Error::new(
+            Span::call_site(),
+            "`requires` and `without` are only supported by `#[entity_command]`",
+        ));
+    }
+    if let (true, Some(unique)) = (once, &unique) {
+        return Err(Error::new(
+            unique.span(),
+            "`unique` and `once` cannot be used together",
+        ));
+    }
+    if let Some(field) = key.iter().find(|field| {
+        !def_field_names
+            .iter()
+            .any(|name| name.to_string() == field.to_string())
+    }) {
+        return Err(Error::new(
+            field.span(),
+            format!("`key` must name one of the command's fields, found `{field}`"),
+        ));
+    }
+    if let (true, Some(merge)) = (once || unique.is_some(), &merge) {
+        return Err(Error::new(
+            merge.span(),
+            "`merge` cannot be used with `unique` or `once`",
+        ));
+    }
+    // keyed commands are unique unless they run once or are merged
+    let unique = unique.or_else(|| {
+        (!key.is_empty() && !once && merge.is_none())
+            .then(|| Ident::new("first", Span::call_site()))
+    });
+    if event && entity_command {
+        return Err(Error::new(
+            Span::call_site(),
+            "`event` is only supported by `#[command]`",
+        ));
+    }
+    if event && !generics.params.is_empty() {
+        return Err(Error::new(
+            generics.span(),
+            "`event` does not support generic commands",
+        ));
+    }
+    if let (false, Some(map_entities)) = (serde, &map_entities) {
+        return Err(Error::new(
+            map_entities.span(),
+            "`map_entities` requires `#[command(serde)]`",
+        ));
+    }
+
+    if entity_command && entity.is_none() {
+        return Err(Error::new(
+            Span::call_site(),
+            "Entity commands must take in a `Entity` parameter",
+        ));
+    }
+
+    // generate fragments to be combined later
+
+    let generic_names = if generic_names.is_empty() {
+        quote!()
+    } else {
+        quote!(< #(#generic_names,)* >)
+    };
+
+    // piece back the original system sans return type
+    let fn_frag = quote!(
+        #[allow(unused)]
+        #(#attrs)*
+        #vis
+        #constness
+        #asyncness
+        #unsafety
+        #abi
+        #fn_token
+        #ident
+        #generics
+        (#inputs)
+        #variadic
+        #block
+    );
+
+    // which trait we're implementing for
+    let command_trait = if entity_command {
+        quote!(EntityCommand)
+    } else {
+        quote!(Command)
+    };
+
+    let return_frag = if do_return { quote!(self) } else { quote!() };
+
+    // the fields of our generated struct
+    let struct_fields_frag = if fields.is_empty() {
+        quote!( ; )
+    } else {
+        quote!( { #(#field_attrs pub #fields,)* } )
+    };
+
+    // derives serde traits for our struct, and maps any `Entity` fields after deserializing
+    let serde_frag = if serde {
+        let map_frag = match &map_entities {
+            Some(map_entities) => quote!(#map_entities(self, entity_mapper);),
+            None => def_field_names
+                .iter()
+                .zip(&field_types)
+                .map(|(name, ty)| map_entity_frag(&syn::parse2(ty.clone())?, quote!(self.#name)))
+                .collect::<Result<TokenStream, Error>>()?,
+        };
+
+        quote!(
+            impl #generics #ecs_root ::entity::MapEntities for #struct_name #generic_names {
+                #[allow(unused_variables)]
+                fn map_entities<M: #ecs_root ::entity::EntityMapper>(&mut self, entity_mapper: &mut M) {
+                    #map_frag
+                }
+            }
+        )
+    } else {
+        quote!()
+    };
+    let serde_derive_frag = if serde {
+        quote!(#[derive(::serde::Serialize, ::serde::Deserialize)])
+    } else {
+        quote!()
+    };
+    // undoable commands are cloned so that they may be redone, system adapters each time they run, and events as they are read
+    let mut derives = Vec::<TokenStream>::new();
+    if debug {
+        derives.push(quote!(::std::fmt::Debug));
+    }
+    if clone || system_adapter || event || undo.is_some() {
+        derives.push(quote!(::std::clone::Clone));
+    }
+    let derive_frag = if derives.is_empty() {
+        quote!()
+    } else {
+        quote!(#[derive(#(#derives,)*)])
+    };
+
+    // extra capabilities stored alongside our command in the `CommandRegistry`
+    let mut registration_frags = Vec::<TokenStream>::new();
+
+    // parses our struct's fields from console arguments, in order
+    let console_frag = if console {
+        registration_frags.push(quote!(
+            .with_console(<Self as ::bevy_commandify::console::ConsoleCommand>::from_console_args)
+        ));
+
+        let (subject_frag, boxed_frag) = if entity_command {
+            (
+                quote!(let entity = args.subject()?;),
+                quote!(::bevy_commandify::console::boxed_entity_command(
+                    command, entity
+                )),
+            )
+        } else {
+            (
+                quote!(),
+                quote!(::bevy_commandify::console::boxed_command(command)),
+            )
+        };
+
+        quote!(
+            impl #generics ::bevy_commandify::console::ConsoleCommand for #struct_name #generic_names {
+                fn from_console_args(
+                    args: &mut ::bevy_commandify::console::ConsoleArgs,
+                ) -> Result<::bevy_commandify::console::DynCommand, ::bevy_commandify::console::ConsoleError> {
+                    #subject_frag
+                    let command = Self { #(#def_field_names: args.field()?,)* };
+                    args.finish()?;
+                    Ok(#boxed_frag)
+                }
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    // bounds required for our command's registration
+    let mut meta_where = Vec::<TokenStream>::new();
+
+    // deserializes our command from JSON
+    let json_frag = if serde {
+        registration_frags.push(quote!(
+            .with_json(<Self as ::bevy_commandify::json::JsonCommand>::from_json)
+            .with_migrate(<Self as ::bevy_commandify::json::JsonCommand>::migrate)
+        ));
+
+        // upgrades through each migration in turn, eg. v1 -> v2 -> v3
+        let name_str = name.to_string();
+        let unsupported_frag = quote!(
+            ::bevy_commandify::json::JsonError::UnsupportedVersion(#name_str, version)
+        );
+        let migrate_body = if migrations.is_empty() {
+            quote!(
+                if version != #version {
+                    return Err(#unsupported_frag);
+                }
+                Ok(args)
+            )
+        } else {
+            let migrate_froms = migrations.iter().map(|(from, _)| from);
+            let migrate_paths = migrations.iter().map(|(_, path)| path);
+            quote!(
+                let mut args = args;
+                let mut from = version;
+                while from < #version {
+                    args = match from {
+                        #(#migrate_froms => #migrate_paths(args),)*
+                        _ => return Err(#unsupported_frag),
+                    };
+                    from += 1;
+                }
+                Ok(args)
+            )
+        };
+
+        let boxed_frag = if entity_command {
+            quote!(::bevy_commandify::console::boxed_entity_command(
+                command,
+                ::bevy_commandify::json::subject::<Self>(entity)?,
+            ))
+        } else {
+            quote!(::bevy_commandify::console::boxed_command(command))
+        };
+
+        meta_where.push(quote!(Self: ::serde::de::DeserializeOwned));
+
+        quote!(
+            impl #generics ::bevy_commandify::json::JsonCommand for #struct_name #generic_names
+            where
+                Self: ::serde::de::DeserializeOwned,
+            {
+                #[allow(unused_variables)]
+                fn from_json(
+                    entity: Option<#ecs_root ::entity::Entity>,
+                    args: ::bevy_commandify::json::Value,
+                ) -> Result<::bevy_commandify::console::DynCommand, ::bevy_commandify::json::JsonError> {
+                    let command = ::bevy_commandify::json::from_value::<Self>(args)?;
+                    Ok(#boxed_frag)
+                }
+
+                fn migrate(
+                    version: u32,
+                    args: ::bevy_commandify::json::Value,
+                ) -> Result<::bevy_commandify::json::Value, ::bevy_commandify::json::JsonError> {
+                    #migrate_body
+                }
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    // deserializes our command from a replication client, mapping its entities to our own
+    let replicate_frag = if replicate {
+        registration_frags.push(quote!(
+            .with_replicate(<Self as ::bevy_commandify::replicate::ReplicatedCommand>::from_replicated)
+        ));
+
+        let boxed_frag = if entity_command {
+            quote!(::bevy_commandify::console::boxed_entity_command(
+                command,
+                #ecs_root ::entity::EntityMapper::map_entity(
+                    mapper,
+                    ::bevy_commandify::json::subject::<Self>(entity)?,
+                ),
+            ))
+        } else {
+            quote!(::bevy_commandify::console::boxed_command(command))
+        };
+
+        quote!(
+            impl #generics ::bevy_commandify::replicate::ReplicatedCommand for #struct_name #generic_names
+            where
+                Self: ::serde::de::DeserializeOwned,
+            {
+                #[allow(unused_variables)]
+                fn from_replicated(
+                    entity: Option<#ecs_root ::entity::Entity>,
+                    args: ::bevy_commandify::json::Value,
+                    mapper: &mut ::bevy_commandify::replicate::ReplicationMapper,
+                ) -> Result<::bevy_commandify::console::DynCommand, ::bevy_commandify::json::JsonError> {
+                    let mut command = ::bevy_commandify::json::from_value::<Self>(args)?;
+                    #ecs_root ::entity::MapEntities::map_entities(&mut command, mapper);
+                    Ok(#boxed_frag)
+                }
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    // describes our serialized fields as a JSON schema
+    let schema_frag = if schema {
+        registration_frags.push(quote!(
+            .with_schema(<Self as ::bevy_commandify::schema::CommandSchema>::json_schema)
+        ));
+
+        let mut field_frags = Vec::new();
+        let mut schema_types = Vec::new();
+        for (((name, ty), attrs), docs) in def_field_names
+            .iter()
+            .zip(&field_types)
+            .zip(&field_attrs)
+            .zip(&field_docs)
+        {
+            let serde_field = parse::serde_field(attrs)?;
+            if serde_field.skip {
+                continue;
+            }
+            let name = serde_field.rename.unwrap_or_else(|| name.to_string());
+            let default = match serde_field.default {
+                Some(Some(path)) => quote!(Some(::bevy_commandify::schema::to_value(#path()))),
+                Some(None) => {
+                    quote!(Some(::bevy_commandify::schema::to_value(<#ty as Default>::default())))
+                }
+                None => quote!(None),
+            };
+            let required = !serde_field.skip_serializing;
+            field_frags.push(quote!(schema.field::<#ty>(#name, #docs, #default, #required);));
+            schema_types.push(ty);
+        }
+        let schema_where = quote!(#(#schema_types: ::bevy_commandify::schema::JsonSchema,)*);
+        meta_where.extend(
+            schema_types
+                .iter()
+                .map(|ty| quote!(#ty: ::bevy_commandify::schema::JsonSchema)),
+        );
+
+        quote!(
+            impl #generics ::bevy_commandify::schema::CommandSchema for #struct_name #generic_names
+            where
+                #schema_where
+            {
+                fn json_schema() -> ::bevy_commandify::json::Value {
+                    #[allow(unused_mut)]
+                    let mut schema = ::bevy_commandify::schema::ObjectSchema::new::<Self>();
+                    #(#field_frags)*
+                    schema.finish()
+                }
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    // describes our command for the `CommandRegistry`
+    let meta_frag = {
+        let name_str = name.to_string();
+        let struct_str = struct_name.to_string();
+        let kind = if entity_command {
+            quote!(EntityCommand)
+        } else {
+            quote!(Command)
+        };
+        let param_names = def_field_names.iter().map(|name| name.to_string());
+        let param_types = field_types.iter().map(parse::type_string);
+        let doc_str = parse::doc_string(&attrs);
+        let meta_where_frag = if meta_where.is_empty() {
+            quote!()
+        } else {
+            quote!(where #(#meta_where,)*)
+        };
+        let registration_frag = if registration_frags.is_empty() {
+            quote!()
+        } else {
+            quote!(
+                fn registration() -> ::bevy_commandify::registry::CommandRegistration {
+                    ::bevy_commandify::registry::CommandRegistration::of::<Self>()
+                        #(#registration_frags)*
+                }
+            )
+        };
+
+        quote!(
+            impl #generics ::bevy_commandify::registry::CommandMeta for #struct_name #generic_names #meta_where_frag {
+                const INFO: ::bevy_commandify::registry::CommandInfo = ::bevy_commandify::registry::CommandInfo {
+                    name: #name_str,
+                    struct_name: #struct_str,
+                    kind: ::bevy_commandify::registry::CommandKind:: #kind,
+                    params: &[#(::bevy_commandify::registry::ParamInfo { name: #param_names, ty: #param_types },)*],
+                    docs: #doc_str,
+                    id: #id,
+                    version: #version,
+                };
+                #registration_frag
+            }
+        )
+    };
+
+    // The inputs passed to our system
+    let system_in_frag = match &args {
+        SystemArgs::Exclusive { .. } => quote!(),
+        SystemArgs::System { systems_in, .. } => {
+            if systems_in.len() > 1 {
+                quote!((#(#systems_in,)*))
+            } else if let Some(field) = systems_in.last() {
+                quote!(#field)
+            } else {
+                quote!()
+            }
+        }
+    };
+
+    // statements run at the start of `apply`, before our command's body
+    let mut apply_hooks = Vec::<TokenStream>::new();
+    let world_ident = match &args {
+        SystemArgs::Exclusive { world_ident, .. } => world_ident.clone(),
+        SystemArgs::System { .. } => Some(Ident::new("world", Span::call_site())),
+    };
+    let hook_entity_frag = match &entity_ident {
+        Some(entity) if entity_command => quote!(Some(#entity)),
+        _ => quote!(None),
+    };
+
+    // takes where our command was issued from, before any of the hooks below may apply commands of their own
+    let name_str = name.to_string();
+    apply_hooks.push(quote!(
+        let __commandify_caller = ::bevy_commandify::flight::caller();
+    ));
+
+    // buffers our command into the `CommandPriorityQueue`, so that its conditions are checked when it is finally applied
+    if priority.is_some() || !after.is_empty() {
+        let Some(world) = &world_ident else {
+            return Err(Error::new(
+                inputs.span(),
+                "commands with `priority` or `after` require the `World` parameter to be named",
+            ));
+        };
+        let boxed_frag = match (entity_command, &entity_ident) {
+            (false, _) => quote!(::bevy_commandify::console::boxed_command(self)),
+            (true, Some(entity)) => {
+                quote!(::bevy_commandify::console::boxed_entity_command(self, #entity))
+            }
+            (true, None) => {
+                return Err(Error::new(
+                    inputs.span(),
+                    "entity commands with `priority` or `after` require the `Entity` parameter to be named",
+                ))
+            }
+        };
+        let priority = priority.unwrap_or_default();
+        // each name resolves to the const generated alongside that command, so that typos fail to compile
+        let after = after.iter().map(|path| {
+            let mut path = path.clone();
+            if let Some(last) = path.segments.last_mut() {
+                last.ident = name_const(&last.ident);
+            }
+            path
+        });
+        apply_hooks.push(quote!(
+            if ::bevy_commandify::priority::defer(#world) {
+                ::bevy_commandify::priority::push(
+                    #world,
+                    #name_str,
+                    #priority,
+                    &[#(#after),*],
+                    #hook_entity_frag,
+                    ::bevy_commandify::flight::track(__commandify_caller, #boxed_frag),
+                );
+                return;
+            }
+        ));
+    }
+
+    // what happens when our command is skipped, `reason` formats a `String` describing why
+    let skip_frag = |reason: TokenStream| match on_skip.as_ref().map(Ident::to_string).as_deref() {
+        Some("log") => quote!(
+            ::bevy_commandify::condition::log_skipped(#name_str, &#reason);
+            return;
+        ),
+        Some("panic") => quote!(panic!("command `{}` failed: {}", #name_str, #reason);),
+        _ => quote!(return;),
+    };
+
+    // skips our entity command when its subject does not have the required components
+    if has_filter {
+        let (Some(world), Some(entity)) = (&world_ident, &entity_ident) else {
+            return Err(Error::new(
+                inputs.span(),
+                "commands with `requires` or `without` require the `World` and `Entity` parameters to be named",
+            ));
+        };
+        let filter = format!(
+            "requires({}), without({})",
+            requires
+                .iter()
+                .map(|ty| ty.to_token_stream().to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            without
+                .iter()
+                .map(|ty| ty.to_token_stream().to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+        let skip_frag = skip_frag(quote!(
+            format!("{:?} does not match `{}`", #entity, #filter)
+        ));
+        apply_hooks.push(quote!(
+            let __commandify_matches = #world.get_entity(#entity).is_some_and(|entity_ref| {
+                true #(&& entity_ref.contains::<#requires>())* #(&& !entity_ref.contains::<#without>())*
+            });
+            if !__commandify_matches {
+                #skip_frag
+            }
+        ));
+    }
+
+    // skips our command when its run condition is false
+    if let Some(run_if) = &run_if {
+        let Some(world) = &world_ident else {
+            return Err(Error::new(
+                inputs.span(),
+                "commands with `run_if` require the `World` parameter to be named",
+            ));
+        };
+        let condition = run_if.to_token_stream().to_string();
+        let skip_frag = skip_frag(quote!(format!("`{}` was false", #condition)));
+        apply_hooks.push(quote!(
+            let __commandify_run = ::bevy_commandify::condition::run::<Self, _>(#world, #run_if);
+            if !__commandify_run {
+                #skip_frag
+            }
+        ));
+    }
+
+    // drops or merges duplicates of our command, as identified by its key fields and entity
+    if once || unique.is_some() || merge.is_some() {
+        let Some(world) = &world_ident else {
+            return Err(Error::new(
+                inputs.span(),
+                "commands with `unique`, `once`, `merge` or `key` require the `World` parameter to be named",
+            ));
+        };
+        let key_entity_frag = match (entity_command, &entity_ident) {
+            (false, _) => quote!(),
+            (true, Some(entity)) => quote!(#entity),
+            (true, None) => {
+                return Err(Error::new(
+                    inputs.span(),
+                    "entity commands with `unique`, `once`, `merge` or `key` require the `Entity` parameter to be named",
+                ))
+            }
+        };
+        apply_hooks.push(quote!(
+            let __commandify_key = ::bevy_commandify::dedup::DedupKey::new(
+                #id,
+                (#(::std::clone::Clone::clone(&self.#key),)* #key_entity_frag),
+            );
+        ));
+
+        // combines the pending `last` instance of our command with the `next` one
+        let (last, next) = if entity_command {
+            (quote!(&mut last.0), quote!(next.0))
+        } else {
+            (quote!(last), quote!(next))
+        };
+        let merge_frag = match (&merge, unique.as_ref().map(Ident::to_string).as_deref()) {
+            (Some(merge), _) => Some(quote!(#merge(#last, #next))),
+            (None, Some("last")) => Some(quote!(*#last = #next)),
+            _ => None,
+        };
+        match (merge_frag, unique.is_some()) {
+            (Some(merge_frag), _) => {
+                let (stored_frag, merge_fn_frag, into_command_frag) = if entity_command {
+                    (
+                        quote!((self, #key_entity_frag)),
+                        quote!(|last: &mut (Self, #ecs_root ::entity::Entity), next: (Self, #ecs_root ::entity::Entity)| #merge_frag),
+                        quote!(|(command, entity)| {
+                            ::bevy_commandify::console::boxed_entity_command(command, entity)
+                        }),
+                    )
+                } else {
+                    (
+                        quote!(self),
+                        quote!(|last: &mut Self, next: Self| #merge_frag),
+                        quote!(::bevy_commandify::console::boxed_command),
+                    )
+                };
+                apply_hooks.push(quote!(
+                    if ::bevy_commandify::dedup::defer(#world) {
+                        ::bevy_commandify::dedup::store(
+                            #world,
+                            __commandify_key,
+                            #stored_frag,
+                            __commandify_caller,
+                            #merge_fn_frag,
+                            #into_command_frag,
+                        );
+                        return;
+                    }
+                ));
+            }
+            (None, true) => apply_hooks.push(quote!(
+                if !::bevy_commandify::dedup::first(#world, __commandify_key) {
+                    return;
+                }
+            )),
+            (None, false) => apply_hooks.push(quote!(
+                if !::bevy_commandify::dedup::once(#world, __commandify_key) {
+                    return;
+                }
+            )),
+        }
+    }
+
+    // records our command into the `FlightRecorder`, if enabled, now that it has not been deferred or skipped
+    let debug_frag = if debug {
+        quote!(Some(&self as &dyn ::std::fmt::Debug))
+    } else {
+        quote!(None)
+    };
+    apply_hooks.push(quote!(
+        ::bevy_commandify::flight::record(#name_str, #hook_entity_frag, #debug_frag, __commandify_caller);
+    ));
+
+    // sends our command to the server instead of applying it, if we are a replication client
+    if replicate {
+        let Some(world) = &world_ident else {
+            return Err(Error::new(
+                inputs.span(),
+                "replicated commands require the `World` parameter to be named",
+            ));
+        };
+        apply_hooks.push(quote!(
+            if ::bevy_commandify::replicate::send(#world, &self, #hook_entity_frag) {
+                return;
+            }
+        ));
+    }
+
+    // records the inverse of our command into the `UndoHistory`, if one exists
+    if let Some(undo) = &undo {
+        let Some(world) = &world_ident else {
+            return Err(Error::new(
+                inputs.span(),
+                "undoable commands require the `World` parameter to be named",
+            ));
+        };
+        let (redo_frag, undo_frag) = match (entity_command, &entity_ident) {
+            (false, _) => (
+                quote!(::bevy_commandify::console::boxed_command(
+                    ::std::clone::Clone::clone(&self)
+                )),
+                quote!(::bevy_commandify::console::boxed_command(#undo(&self, world))),
+            ),
+            (true, Some(entity)) => (
+                quote!(::bevy_commandify::console::boxed_entity_command(::std::clone::Clone::clone(&self), #entity)),
+                quote!(::bevy_commandify::console::boxed_command(#undo(&self, #entity, world))),
+            ),
+            (true, None) => {
+                return Err(Error::new(
+                    inputs.span(),
+                    "undoable entity commands require the `Entity` parameter to be named",
+                ))
+            }
+        };
+        let docs = parse::doc_string(&attrs);
+        let label = docs
+            .lines()
+            .next()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.trim().to_string())
+            .unwrap_or_else(|| name.to_string());
+        apply_hooks.push(quote!(
+            let __commandify_undo = ::bevy_commandify::undo::record(
+                #world,
+                #label,
+                || #redo_frag,
+                |world| #undo_frag,
+            );
+        ));
+    }
+
+    // records our command into the `CommandJournal`, if one exists
+    if serde {
+        let Some(world) = &world_ident else {
+            return Err(Error::new(
+                inputs.span(),
+                "serde commands require the `World` parameter to be named",
+            ));
+        };
+        apply_hooks.push(quote!(
+            let __commandify_journal = ::bevy_commandify::journal::record(#world, &self, #hook_entity_frag);
+        ));
+    }
+
+    let command_where_frag = if derives.is_empty() {
+        quote!()
+    } else {
+        quote!(where Self: #(#derives)+*)
+    };
+
+    // Generates a `Commands` or `EntityCommands` impl for our struct
+    let (apply_params, body_frag) = match &args {
+        SystemArgs::Exclusive { world, .. } => {
+            let apply_params = if entity_command {
+                quote!((self, #entity, #world))
+            } else {
+                quote!((self, #world))
+            };
+            (
+                apply_params,
+                quote!(
+                    let #struct_name {#(#impl_field_names,)*} = self;
+                    #block
+                ),
+            )
+        }
+        SystemArgs::System { .. } => {
+            let apply_params = if entity_command {
+                quote!((self, #entity, world: &mut #ecs_root ::world::World))
+            } else {
+                quote!((self, world: &mut #ecs_root ::world::World))
+            };
+            let body_frag = if fields.is_empty() {
+                quote!(
+                    use #ecs_root ::system::RunSystemOnce;
+                    world.run_system_once(#ident);
+                )
+            } else {
+                quote!(
+                    use #ecs_root ::system::RunSystemOnce;
+                    let #struct_name {#(#def_field_names,)*} = self;
+                    world.run_system_once_with(#system_in_frag, #ident);
+                )
+            };
+            (apply_params, body_frag)
+        }
+    };
+    let impl_command_frag = quote!(
+        impl #generics #ecs_root ::system:: #command_trait for #struct_name #generic_names #command_where_frag {
+            fn apply #apply_params {
+                #(#apply_hooks)*
+                #body_frag
+            }
+        }
+    );
+
+    // Generates `foo_after` and `foo_at_tick` methods with `delay`, scheduling our command to be applied later
+    // avoids shadowing any of our own fields with the same name
+    let delay_param = |param: &str| {
+        let taken = def_field_names
+            .iter()
+            .any(|field| field.to_string() == param);
+        let param = if taken {
+            format!("__{param}")
+        } else {
+            param.to_string()
+        };
+        Ident::new(&param, Span::call_site())
+    };
+    let delay_arg = delay_param("delay");
+    let tick_arg = delay_param("tick");
+    let after_name = Ident::new(&format!("{name}_after"), name.span());
+    let at_tick_name = Ident::new(&format!("{name}_at_tick"), name.span());
+    let after_doc = format!(
+        "Applies [`{name}`](Self::{name}) once `delay` has elapsed, see `CommandScheduler`"
+    );
+    let at_tick_doc =
+        format!("Applies [`{name}`](Self::{name}) once the `CommandScheduler` reaches `tick`");
+    let delay_decls = if delay {
+        quote!(
+            #[doc = #after_doc]
+            fn #after_name #generics (&mut self, #delay_arg: ::std::time::Duration, #(#fields,)*) -> ::bevy_commandify::delay::CommandToken;
+            #[doc = #at_tick_doc]
+            fn #at_tick_name #generics (&mut self, #tick_arg: u64, #(#fields,)*) -> ::bevy_commandify::delay::CommandToken;
+        )
+    } else {
+        quote!()
+    };
+    // `issue` queues or applies the `command` variable, depending on what we're implemented for
+    let delay_impls = |issue: TokenStream| {
+        if !delay {
+            return quote!();
+        }
+        quote!(
+            #[track_caller]
+            fn #after_name #generics (&mut self, #delay_arg: ::std::time::Duration, #(#fields,)*) -> ::bevy_commandify::delay::CommandToken {
+                let token = ::bevy_commandify::delay::CommandToken::new();
+                let command = ::bevy_commandify::delay::Scheduled::new(
+                    ::bevy_commandify::delay::Delay::After(#delay_arg),
+                    token.clone(),
+                    ::bevy_commandify::flight::Tracked::new(#struct_name {#(#def_field_names,)*}),
+                );
+                #issue
+                token
+            }
+
+            #[track_caller]
+            fn #at_tick_name #generics (&mut self, #tick_arg: u64, #(#fields,)*) -> ::bevy_commandify::delay::CommandToken {
+                let token = ::bevy_commandify::delay::CommandToken::new();
+                let command = ::bevy_commandify::delay::Scheduled::new(
+                    ::bevy_commandify::delay::Delay::AtTick(#tick_arg),
+                    token.clone(),
+                    ::bevy_commandify::flight::Tracked::new(#struct_name {#(#def_field_names,)*}),
+                );
+                #issue
+                token
+            }
+        )
+    };
+    let delay_queue_impls = delay_impls(quote!(self.add(command);));
+
+    // Generates `foo_system(args)`, running our fn as a regular system with the given args each time it runs
+    // Bypasses the hooks in `apply`, since the system is not an issued command
+    let system_adapter_frag = if system_adapter {
+        let adapter_name = Ident::new(&format!("{name}_system"), name.span());
+        let adapter_doc = format!("Runs [`{ident}`] as a system with the given args, eg. `app.add_systems(Update, {adapter_name}(...))`");
+        let entity_param = entity.as_ref().map(|entity| quote!(#entity,));
+        let system_frag = match &args {
+            SystemArgs::Exclusive { world, .. } => quote!(
+                #ecs_root ::system::IntoSystem::into_system(move |#world| {
+                    let #struct_name {#(#impl_field_names,)*} = ::std::clone::Clone::clone(&command);
+                    #block
+                })
+            ),
+            SystemArgs::System { .. } if def_field_names.is_empty() && entity_ident.is_none() => {
+                quote!(
+                    #ecs_root ::system::IntoSystem::into_system(#ident)
+                )
+            }
+            SystemArgs::System { .. } => quote!(
+                #ecs_root ::system::IntoSystem::into_system(#ecs_root ::system::IntoSystem::pipe(
+                    move || {
+                        let #struct_name {#(#def_field_names,)*} = ::std::clone::Clone::clone(&command);
+                        #system_in_frag
+                    },
+                    #ident,
+                ))
+            ),
+        };
+        quote!(
+            #[doc = #adapter_doc]
+            #[allow(unused)]
+            #vis fn #adapter_name #generics (#entity_param #(#fields,)*) -> impl #ecs_root ::system::System<In = (), Out = ()> {
+                let command = #struct_name {#(#def_field_names,)*};
+                #system_frag
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    // Generates a new trait + method for issuing our command
+    // Implements this new trait for `Commands` or `EntityCommands`
+    let commands_trait_frag = match &args {
+        SystemArgs::Exclusive { .. } => {
+            if no_trait {
+                quote!()
+            } else {
+                let commands_struct = if entity_command {
+                    quote!(EntityCommands<'_>)
+                } else {
+                    quote!(Commands<'_, '_>)
+                };
+                quote!(
+                    pub trait #trait_name {
+                        #docs
+                        fn #name #generics (&mut self, #(#fields,)*) #output;
+                        #delay_decls
+                    }
+
+                    impl #trait_name for #ecs_root ::system:: #commands_struct {
+                        #[track_caller]
+                        fn #name #generics (&mut self, #(#fields,)*) #output {
+                            self.add(::bevy_commandify::flight::Tracked::new(#struct_name {#(#def_field_names,)*}));
+                            #return_frag
+                        }
+
+                        #delay_queue_impls
+                    }
+                )
+            }
+        }
+        SystemArgs::System { .. } => {
+            if no_trait {
+                quote!()
+            } else {
+                let commands_struct = if entity_command {
+                    quote!(EntityCommands<'_>)
+                } else {
+                    quote!(Commands<'_, '_>)
+                };
+
+                quote!(
+                    pub trait #trait_name {
+                        #docs
+                        fn #name #generics (&mut self #(,#fields,)*) #output;
+                        #delay_decls
+                    }
+
+                    impl #trait_name for #ecs_root ::system:: #commands_struct {
+                        #[track_caller]
+                        fn #name #generics (&mut self #(,#fields,)*) #output {
+                            self.add(::bevy_commandify::flight::Tracked::new(#struct_name {#(#def_field_names,)*}));
+                            #return_frag
+                        }
+
+                        #delay_queue_impls
+                    }
+                )
+            }
+        }
+    };
+
+    // Implements the same trait as above for `CommandSender` or `EntityCommandSender`, sending our command to be applied later
+    let impl_sender_frag = if no_trait {
+        quote!()
+    } else {
+        let sender_struct = if entity_command {
+            quote!(EntityCommandSender<'_>)
+        } else {
+            quote!(CommandSender)
+        };
+        quote!(
+            impl #trait_name for ::bevy_commandify::sender:: #sender_struct {
+                #[track_caller]
+                fn #name #generics (&mut self, #(#fields,)*) #output {
+                    self.add(::bevy_commandify::flight::Tracked::new(#struct_name {#(#def_field_names,)*}));
+                    #return_frag
+                }
+
+                #delay_queue_impls
+            }
+        )
+    };
+
+    // Implements `Event` for our struct, and generates a trait + method for sending it via `EventWriter`
+    let event_frag = if event {
+        let event_trait_name = Ident::new(
+            &format!("EventWriter{}Ext", name.to_string().to_pascal_case()),
+            name.span(),
+        );
+        let event_trait_frag = if no_trait {
+            quote!()
+        } else {
+            quote!(
+                pub trait #event_trait_name {
+                    #docs
+                    fn #name (&mut self, #(#fields,)*) #output;
+                }
+
+                impl #event_trait_name for #ecs_root ::event::EventWriter<'_, #struct_name> {
+                    fn #name (&mut self, #(#fields,)*) #output {
+                        self.send(#struct_name {#(#def_field_names,)*});
+                        #return_frag
+                    }
+                }
+            )
+        };
+        quote!(
+            impl #ecs_root ::event::Event for #struct_name {}
+
+            #event_trait_frag
+        )
+    } else {
+        quote!()
+    };
+
+    // Implements the same trait as above, but for `World` or `EntityWorldMut`
+    // Applies our command immediately, so that it behaves the same as when queued
+    let impl_world_frag = if no_trait || no_world {
+        quote!()
+    } else {
+        let tracked = quote!(::bevy_commandify::flight::Tracked<#struct_name #generic_names>);
+        let delay_world_impls = if entity_command {
+            delay_impls(quote!(
+                let id = self.id();
+                self.world_scope(|world| {
+                    #ecs_root ::system::EntityCommand::apply(command, id, world);
+                });
+            ))
+        } else {
+            delay_impls(quote!(#ecs_root ::system::Command::apply(command, self);))
+        };
+        if entity_command {
+            quote!(
+                impl #trait_name for #ecs_root ::world::EntityWorldMut<'_> {
+                    #[track_caller]
+                    fn #name #generics (&mut self, #(#fields,)*) #output {
+                        let id = self.id();
+                        let command = ::bevy_commandify::flight::Tracked::new(#struct_name {#(#def_field_names,)*});
+                        self.world_scope(|world| {
+                            <#tracked as #ecs_root ::system:: #command_trait>::apply(command, id, world);
+                        });
+                        #return_frag
+                    }
+
+                    #delay_world_impls
+                }
+            )
+        } else {
+            quote!(
+                impl #trait_name for #ecs_root ::world::World {
+                    #[track_caller]
+                    fn #name #generics (&mut self, #(#fields,)*) #output {
+                        let command = ::bevy_commandify::flight::Tracked::new(#struct_name {#(#def_field_names,)*});
+                        <#tracked as #ecs_root ::system:: #command_trait>::apply(command, self);
+                        #return_frag
+                    }
+
+                    #delay_world_impls
+                }
+            )
+        }
+    };
+
+    // our command's name, referenced by the `after` of other commands
+    let name_const_ident = name_const(&name);
+    let name_const_frag = quote!(
+        #[doc(hidden)]
+        #[allow(dead_code, non_upper_case_globals)]
+        #vis const #name_const_ident: &str = #name_str;
+    );
+
+    Ok(quote!(
+        #fn_frag
+        #name_const_frag
+        #(#attrs)*
+        #serde_derive_frag
+        #derive_frag
+        #vis
+        #constness
+        #asyncness
+        #unsafety
+        #abi
+        struct
+        #struct_name
+        #generics
+        #struct_fields_frag
+        #impl_command_frag
+        #serde_frag
+        #console_frag
+        #json_frag
+        #replicate_frag
+        #schema_frag
+        #meta_frag
+        #system_adapter_frag
+        #commands_trait_frag
+        #impl_sender_frag
+        #event_frag
+        #impl_world_frag
+    ))
+}
+
+/// Maps each `Entity` within `place`, a field of type `ty` or a value within one
+///
+/// Options, boxes, vecs, arrays and tuples are mapped through, while other types holding entities must be mapped via `map_entities`
+fn map_entity_frag(ty: &syn::Type, place: TokenStream) -> Result<TokenStream, Error> {
+    if !parse::contains_entity(ty) {
+        return Ok(quote!());
+    }
+    // each value within a container, mapped in turn
+    let each_frag = |elem: &syn::Type, values: TokenStream| -> Result<TokenStream, Error> {
+        let value_frag = map_entity_frag(elem, quote!((*__commandify_value)))?;
+        Ok(quote!(
+            for __commandify_value in #values {
+                #value_frag
+            }
+        ))
+    };
+    match ty {
+        syn::Type::Paren(paren) => map_entity_frag(&paren.elem, place),
+        syn::Type::Group(group) => map_entity_frag(&group.elem, place),
+        syn::Type::Tuple(tuple) => tuple
+            .elems
+            .iter()
+            .enumerate()
+            .map(|(index, elem)| {
+                let index = syn::Index::from(index);
+                map_entity_frag(elem, quote!(#place.#index))
+            })
+            .collect(),
+        syn::Type::Array(array) => each_frag(&array.elem, quote!(#place.iter_mut())),
+        syn::Type::Path(path) if path.qself.is_none() => {
+            let last = path.path.segments.last().unwrap();
+            let arg = match &last.arguments {
+                syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+                    match &args.args[0] {
+                        syn::GenericArgument::Type(arg) => Some(arg),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            match (last.ident.to_string().as_str(), arg) {
+                ("Entity", None) => Ok(quote!(#place = entity_mapper.map_entity(#place);)),
+                ("Option", Some(arg)) => {
+                    let value_frag = map_entity_frag(arg, quote!((*__commandify_value)))?;
+                    Ok(quote!(
+                        if let Some(__commandify_value) = &mut #place {
+                            #value_frag
+                        }
+                    ))
+                }
+                ("Box", Some(arg)) => map_entity_frag(arg, quote!((*#place))),
+                ("Vec" | "VecDeque", Some(arg)) => each_frag(arg, quote!(#place.iter_mut())),
+                _ => Err(unmapped_entity(ty)),
+            }
+        }
+        _ => Err(unmapped_entity(ty)),
+    }
+}
+
+fn unmapped_entity(ty: &syn::Type) -> Error {
+    Error::new(
+        ty.span(),
+        "entities within this type cannot be mapped, map them via `map_entities = T` instead",
+    )
+}
+
+/// The hidden const holding the name of the command `name`
+fn name_const(name: &Ident) -> Ident {
+    Ident::new(&format!("__commandify_{name}"), name.span())
+}
@@ -0,0 +1,147 @@
+mod gen;
+mod parse;
+
+use gen::commandify;
+
+use proc_macro::TokenStream as ProcTokenStream;
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, Error, ItemFn, Meta};
+
+/// Promotes a function to a `Command` struct, and creates an equivalent `Commands` and `World` method via trait extensions
+///
+/// The trait is also implemented for `CommandSender`, so that the command may be sent from other threads
+///
+/// - `#[command(no_trait)]` prevents generating a trait method for `Commands`
+/// - `#[command(no_world)]` prevents generating a trait impl for `World`
+/// - `#[command(name = T)]` will use this name for the method and related struct/trait names
+/// - `#[command(struct_name = T)]` will use this name for the generated struct, defaults to `<Foo>Command`
+/// - `#[command(trait_name = T)]` will use this name for the generated trait, defaults to `Commands<Foo>Ext`
+/// - `#[command(ecs = T)]` to change the crate root to T, defaults to `bevy::ecs`
+/// - `#[command(bevy_ecs)]` to change the crate root to `bevy_ecs`
+/// - `#[command(serde)]` derives `Serialize` and `Deserialize` for the generated struct, requires the `serde` feature
+///   - `#[serde(...)]` attributes on fn parameters are passed through to the struct's fields
+///   - `Entity` fields are remapped via the generated `MapEntities` impl, including entities within `Option`, `Box`, `Vec`, `VecDeque`, arrays and tuples
+///   - fields holding entities within other types fail to compile, and must be mapped via `map_entities`
+///   - Applied commands are recorded by the `CommandJournal` if present
+/// - `#[command(replicate)]` sends the command to the server instead of applying it when the world has a `ReplicationClient`, requires `serde`
+/// - `#[command(id = N)]` sets the stable id used to find serialized commands, at most `2^53 - 1`, defaults to a hash of the name
+/// - `#[command(version = N)]` sets the version of the serialized fields, defaults to 1 or the version after the newest migration
+/// - `#[command(migrate_from(v1 = T, ...))]` calls `T(Value) -> Value` to upgrade args serialized at v1 to v2, and so on, requires `serde`
+/// - `#[command(map_entities = T)]` will call `T(&mut self, &mut impl EntityMapper)` to map entities instead
+/// - `#[command(console)]` allows the command to be run from a command line, each field must implement `ConsoleArg`
+/// - `#[command(schema)]` implements `CommandSchema` for the generated struct, requires `serde` and that each field implements `JsonSchema`
+///   - doc comments on fn parameters describe their field
+///   - `#[serde(skip)]` fields are omitted, and `#[serde(flatten)]` fields are not supported
+/// - `#[command(debug)]` derives `Debug` for the generated struct, so that its fields are shown by the `FlightRecorder`
+/// - `#[command(event)]` implements `Event` for the generated struct, and generates an `EventWriter` trait method sending it
+///   - commands sent as events are applied once added via `add_command_event`
+///   - derives `Clone` for the generated struct, so that the events are left for other systems to read
+/// - `#[command(run_if = T)]` runs the condition system `T` before applying, and skips the command if it returns false
+///   - `T` is initialized the first time the command is applied, and kept for each later application
+///   - `#[command(on_skip = log)]` or `#[command(on_skip = panic)]` to log a warning or panic when skipped, rather than silently skipping
+///   - skipped commands are not replicated, journaled or added to the undo history
+/// - `#[command(unique)]` drops duplicates of the command applied during the same main schedule, such as `Update`, keeping the first
+///   - `#[command(unique = last)]` instead keeps the last, deferring it to the end of the frame
+///   - requires the `CommandDedupPlugin`, otherwise duplicates are applied as usual
+/// - `#[command(once)]` only applies the command once for the lifetime of the world
+/// - `#[command(merge = T)]` calls `T(&mut FooCommand, FooCommand)` to combine instances of the command applied in the same frame, applying the result once at the end of the frame
+///   - requires the `CommandDedupPlugin`, otherwise each instance is applied as usual
+/// - `#[command(key = field)]` or `#[command(key = (a, b, ...))]` only treats commands with equal fields as duplicates, implies `unique` unless `once` or `merge`
+///   - key fields must implement `Clone`, `Eq` and `Hash`
+/// - `#[command(priority = N)]` buffers the command into the `CommandPriorityQueue`, applying the highest priority commands first, defaults to 0
+/// - `#[command(after = T)]` or `#[command(after = (A, B, ...))]` buffers the command, applying it after any buffered commands named `T`
+///   - `T` must be a command in scope, or a path to one such as `combat::damage`, so that typos fail to compile
+///   - buffered commands are applied after each of the main schedules, and their conditions are checked when applied
+///   - with `merge` or `unique = last` too, buffered commands are then merged and applied at the end of the frame
+///   - requires the `CommandPriorityPlugin`, otherwise commands are applied in the order they are issued
+/// - `#[command(system_adapter)]` generates `foo_system(args)`, running the fn as a regular system with the given args each time it runs
+///   - derives `Clone` for the generated struct
+/// - `#[command(delay)]` also generates `foo_after(Duration, ...)` and `foo_at_tick(u64, ...)` methods, scheduling the command via the `CommandScheduler`
+/// - `#[command(clone)]` derives `Clone` for the generated struct, so that it may be bound to inputs via `bind_command`, buttons via `CommandButton`, repeated via `repeat`, or scheduled via `add_command`
+/// - `#[command(undo = T)]` calls `T(&Self, &mut World) -> impl Command` before applying, to capture a command reverting it into the `UndoHistory`
+///   - derives `Clone` for the generated struct, so that it may be redone
+///   - the first line of the fn's doc comment is used as the undo label
+///
+/// Note: `T`s may be optionally quoted
+///
+/// Commands may optionally return `&mut Self` to allow chaining their calls
+///
+/// The generated struct implements `CommandMeta`, so it may be added to the `CommandRegistry`
+#[proc_macro_attribute]
+pub fn command(args: ProcTokenStream, input: ProcTokenStream) -> ProcTokenStream {
+    let args = parse_macro_input!(args with Punctuated::<Meta, syn::Token![,]>::parse_terminated);
+    let item = parse_macro_input!(input as ItemFn);
+
+    commandify(args, item, false)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Promotes a function to an EntityCommand struct, and creates an equivalent EntityCommands method via trait extensions
+///
+/// The trait is also implemented for `EntityCommandSender`, so that the command may be sent from other threads
+///
+/// - `#[entity_command(no_trait)]` prevents generating a trait method for EntityCommands
+/// - `#[entity_command(name = T)]` will use this name for the method and related struct/trait names
+/// - `#[entity_command(struct_name = T)]` will use this name for the generated struct, defaults to `<Foo>EntityCommand`
+/// - `#[entity_command(trait_name = T)]` will use this name for the generated trait, defaults to `EntityCommands<Foo>Ext`
+/// - `#[entity_command(ecs = T)]` to change the crate root to T, defaults to `bevy::ecs`
+/// - `#[entity_command(bevy_ecs)]` to change the crate root to `bevy_ecs`
+/// - `#[entity_command(serde)]` derives `Serialize` and `Deserialize` for the generated struct, requires the `serde` feature
+///   - `#[serde(...)]` attributes on fn parameters are passed through to the struct's fields
+///   - `Entity` fields are remapped via the generated `MapEntities` impl, including entities within `Option`, `Box`, `Vec`, `VecDeque`, arrays and tuples
+///   - fields holding entities within other types fail to compile, and must be mapped via `map_entities`
+///   - Applied commands are recorded by the `CommandJournal` if present
+/// - `#[entity_command(replicate)]` sends the command to the server instead of applying it when the world has a `ReplicationClient`, requires `serde`
+/// - `#[entity_command(id = N)]` sets the stable id used to find serialized commands, at most `2^53 - 1`, defaults to a hash of the name
+/// - `#[entity_command(version = N)]` sets the version of the serialized fields, defaults to 1 or the version after the newest migration
+/// - `#[entity_command(migrate_from(v1 = T, ...))]` calls `T(Value) -> Value` to upgrade args serialized at v1 to v2, and so on, requires `serde`
+/// - `#[entity_command(map_entities = T)]` will call `T(&mut self, &mut impl EntityMapper)` to map entities instead
+/// - `#[entity_command(console)]` allows the command to be run from a command line, each field must implement `ConsoleArg`
+/// - `#[entity_command(schema)]` implements `CommandSchema` for the generated struct, requires `serde` and that each field implements `JsonSchema`
+///   - doc comments on fn parameters describe their field
+///   - `#[serde(skip)]` fields are omitted, and `#[serde(flatten)]` fields are not supported
+/// - `#[entity_command(debug)]` derives `Debug` for the generated struct, so that its fields are shown by the `FlightRecorder`
+/// - `#[entity_command(run_if = T)]` runs the condition system `T` before applying, and skips the command if it returns false
+///   - `T` is initialized the first time the command is applied, and kept for each later application
+/// - `#[entity_command(requires(A, B, ...))]` skips the command unless the entity exists and has every component listed
+/// - `#[entity_command(without(A, B, ...))]` skips the command if the entity has any of the components listed
+/// - `#[entity_command(on_skip = log)]` or `#[entity_command(on_skip = panic)]` to log a warning or panic when skipped by `run_if`, `requires` or `without`, rather than silently skipping
+///   - skipped commands are not replicated, journaled or added to the undo history
+/// - `#[entity_command(unique)]` drops duplicates of the command applied to the same entity during the same main schedule, such as `Update`, keeping the first
+///   - `#[entity_command(unique = last)]` instead keeps the last, deferring it to the end of the frame
+///   - requires the `CommandDedupPlugin`, otherwise duplicates are applied as usual
+/// - `#[entity_command(once)]` only applies the command once for the lifetime of the world
+/// - `#[entity_command(merge = T)]` calls `T(&mut FooEntityCommand, FooEntityCommand)` to combine instances of the command applied for the same entity in the same frame, applying the result once at the end of the frame
+///   - requires the `CommandDedupPlugin`, otherwise each instance is applied as usual
+/// - `#[entity_command(key = field)]` or `#[entity_command(key = (a, b, ...))]` only treats commands with the same entity and equal fields as duplicates, implies `unique` unless `once` or `merge`
+///   - key fields must implement `Clone`, `Eq` and `Hash`
+/// - `#[entity_command(priority = N)]` buffers the command into the `CommandPriorityQueue`, applying the highest priority commands first, defaults to 0
+/// - `#[entity_command(after = T)]` or `#[entity_command(after = (A, B, ...))]` buffers the command, applying it after any buffered commands named `T`
+///   - `T` must be a command in scope, or a path to one such as `combat::damage`, so that typos fail to compile
+///   - buffered commands are applied after each of the main schedules, and their conditions are checked when applied
+///   - with `merge` or `unique = last` too, buffered commands are then merged and applied at the end of the frame
+///   - requires the `CommandPriorityPlugin`, otherwise commands are applied in the order they are issued
+/// - `#[entity_command(system_adapter)]` generates `foo_system(entity, args)`, running the fn as a regular system with the given args each time it runs
+///   - derives `Clone` for the generated struct
+/// - `#[entity_command(delay)]` also generates `foo_after(Duration, ...)` and `foo_at_tick(u64, ...)` methods, scheduling the command via the `CommandScheduler`
+///   - scheduled entity commands are cancelled if their entity is despawned
+/// - `#[entity_command(clone)]` derives `Clone` for the generated struct, so that it may be bound to inputs via `bind_entity_command`, buttons via `EntityCommandButton`, or repeated via `repeat`
+/// - `#[entity_command(undo = T)]` calls `T(&Self, Entity, &mut World) -> impl Command` before applying, to capture a command reverting it into the `UndoHistory`
+///   - derives `Clone` for the generated struct, so that it may be redone
+///   - the first line of the fn's doc comment is used as the undo label
+///
+/// Note: `T`s may be optionally quoted
+///
+/// Commands may optionally return `&mut Self` to allow chaining their calls
+///
+/// The generated struct implements `CommandMeta`, so it may be added to the `CommandRegistry`
+#[proc_macro_attribute]
+pub fn entity_command(args: ProcTokenStream, input: ProcTokenStream) -> ProcTokenStream {
+    let args = parse_macro_input!(args with Punctuated::<Meta, syn::Token![,]>::parse_terminated);
+    let item = parse_macro_input!(input as ItemFn);
+
+    commandify(args, item, true)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
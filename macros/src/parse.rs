@@ -0,0 +1,780 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, ToTokens};
+use std::fmt::Display;
+use std::str::FromStr;
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::token::Comma;
+use syn::{
+    parse_quote, Attribute, Error, Expr, ExprLit, ExprUnary, FnArg, GenericArgument, Lit, LitStr,
+    Meta, MetaNameValue, Pat, Path, PathArguments, ReturnType, Token, Type, UnOp,
+};
+
+pub struct MacroArgs {
+    pub no_trait: bool,
+    pub no_world: bool,
+    pub name: Ident,
+    pub struct_name: Option<Ident>,
+    pub trait_name: Option<Ident>,
+    pub ecs_root: Option<Path>,
+    pub serde: bool,
+    pub map_entities: Option<Path>,
+    pub console: bool,
+    pub schema: bool,
+    pub debug: bool,
+    pub clone: bool,
+    pub event: bool,
+    pub system_adapter: bool,
+    /// generates `foo_after` and `foo_at_tick` methods
+    pub delay: bool,
+    pub run_if: Option<Expr>,
+    /// what happens when `run_if` is false or `requires`/`without` do not match, one of `skip`, `log` or `panic`
+    pub on_skip: Option<Ident>,
+    pub requires: Vec<Type>,
+    pub without: Vec<Type>,
+    /// which duplicate is kept when `unique`, one of `first` or `last`
+    pub unique: Option<Ident>,
+    pub once: bool,
+    pub merge: Option<Path>,
+    pub priority: Option<i32>,
+    /// names of the commands this command is applied after, or paths to them in other modules
+    pub after: Vec<Path>,
+    /// fields identifying duplicates of `unique`/`once` commands
+    pub key: Vec<Ident>,
+    pub undo: Option<Path>,
+    pub replicate: bool,
+    pub id: Option<u64>,
+    pub version: Option<u32>,
+    /// `(version, path)` for each `#[command(migrate_from(vN = path))]`
+    pub migrations: Vec<(u32, Path)>,
+}
+
+/// parse macro args
+pub fn macro_args(args: &Punctuated<Meta, Comma>, mut name: Ident) -> Result<MacroArgs, Error> {
+    // arguments
+    let mut no_trait = false;
+    let mut no_world = false;
+    let mut struct_name = None;
+    let mut trait_name = None;
+    let mut ecs_root = None;
+    let mut serde = false;
+    let mut map_entities = None;
+    let mut console = false;
+    let mut schema = false;
+    let mut debug = false;
+    let mut clone = false;
+    let mut event = false;
+    let mut system_adapter = false;
+    let mut delay = false;
+    let mut run_if = None;
+    let mut on_skip = None;
+    let mut requires = Vec::new();
+    let mut without = Vec::new();
+    let mut unique = None;
+    let mut once = false;
+    let mut merge = None;
+    let mut priority = None;
+    let mut after = Vec::new();
+    let mut key = Vec::new();
+    let mut undo = None;
+    let mut replicate = false;
+    let mut id = None;
+    let mut version = None;
+    let mut migrations = Vec::new();
+
+    // parse macro arguments
+    for meta in args {
+        match meta {
+            Meta::Path(path) if path.is_ident("no_trait") => {
+                no_trait = true;
+            }
+            Meta::Path(path) if path.is_ident("no_world") => {
+                no_world = true;
+            }
+            Meta::Path(path) if path.is_ident("bevy_ecs") => {
+                ecs_root = Some(parse_quote!(::bevy_ecs));
+            }
+            Meta::Path(path) if path.is_ident("console") => {
+                console = true;
+            }
+            Meta::Path(path) if path.is_ident("schema") => {
+                schema = true;
+            }
+            Meta::Path(path) if path.is_ident("debug") => {
+                debug = true;
+            }
+            Meta::Path(path) if path.is_ident("clone") => {
+                clone = true;
+            }
+            Meta::Path(path) if path.is_ident("event") => {
+                event = true;
+            }
+            Meta::Path(path) if path.is_ident("system_adapter") => {
+                system_adapter = true;
+            }
+            Meta::Path(path) if path.is_ident("delay") => {
+                delay = true;
+            }
+            Meta::Path(path) if path.is_ident("unique") => {
+                unique = Some(Ident::new("first", path.span()));
+            }
+            Meta::Path(path) if path.is_ident("once") => {
+                once = true;
+            }
+            Meta::Path(path) if path.is_ident("replicate") => {
+                replicate = true;
+            }
+            Meta::Path(path) if path.is_ident("serde") => {
+                if cfg!(not(feature = "serde")) {
+                    return Err(Error::new(
+                        path.span(),
+                        "`serde` requires the `serde` feature to be enabled",
+                    ));
+                }
+                serde = true;
+            }
+            Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("name") => {
+                name = value.try_to_ident()?;
+            }
+            Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("struct_name") => {
+                struct_name = Some(value.try_to_ident()?);
+            }
+            Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("trait_name") => {
+                trait_name = Some(value.try_to_ident()?);
+            }
+            Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("ecs") => {
+                ecs_root = Some(value.try_to_path()?);
+            }
+            Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("map_entities") => {
+                map_entities = Some(value.try_to_path()?);
+            }
+            Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("run_if") => {
+                run_if = Some(value.clone());
+            }
+            Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("on_skip") => {
+                let ident = value.try_to_ident()?;
+                if !["skip", "log", "panic"].contains(&ident.to_string().as_str()) {
+                    return Err(Error::new(
+                        ident.span(),
+                        "expected one of `skip`, `log` or `panic`",
+                    ));
+                }
+                on_skip = Some(ident);
+            }
+            Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("unique") => {
+                let ident = value.try_to_ident()?;
+                if !["first", "last"].contains(&ident.to_string().as_str()) {
+                    return Err(Error::new(
+                        ident.span(),
+                        "expected one of `first` or `last`",
+                    ));
+                }
+                unique = Some(ident);
+            }
+            Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("merge") => {
+                merge = Some(value.try_to_path()?);
+            }
+            Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("priority") => {
+                priority = Some(value.try_to_int()?);
+            }
+            Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("after") => {
+                after.extend(value.try_to_paths()?);
+            }
+            Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("key") => {
+                key.extend(value.try_to_idents()?);
+            }
+            Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("undo") => {
+                undo = Some(value.try_to_path()?);
+            }
+            Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("id") => {
+                id = Some(value.try_to_int()?);
+            }
+            Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("version") => {
+                version = Some(value.try_to_int()?);
+            }
+            Meta::List(list) if list.path.is_ident("requires") => {
+                requires
+                    .extend(list.parse_args_with(Punctuated::<Type, Token![,]>::parse_terminated)?);
+            }
+            Meta::List(list) if list.path.is_ident("without") => {
+                without
+                    .extend(list.parse_args_with(Punctuated::<Type, Token![,]>::parse_terminated)?);
+            }
+            Meta::List(list) if list.path.is_ident("migrate_from") => {
+                let args =
+                    list.parse_args_with(Punctuated::<MetaNameValue, Token![,]>::parse_terminated)?;
+                for MetaNameValue { path, value, .. } in args {
+                    let from = path
+                        .get_ident()
+                        .and_then(|ident| ident.to_string().strip_prefix('v')?.parse().ok())
+                        .ok_or_else(|| {
+                            Error::new(path.span(), "expected a version such as `v1`")
+                        })?;
+                    migrations.push((from, value.try_to_path()?));
+                }
+            }
+            _ => {
+                return Err(Error::new(
+                    meta.span(),
+                    format!("Unknown attribute `{}`", meta.to_token_stream()),
+                ))
+            }
+        }
+    }
+
+    Ok(MacroArgs {
+        no_trait,
+        no_world,
+        name,
+        struct_name,
+        trait_name,
+        ecs_root,
+        serde,
+        map_entities,
+        console,
+        schema,
+        debug,
+        clone,
+        event,
+        system_adapter,
+        delay,
+        run_if,
+        on_skip,
+        requires,
+        without,
+        unique,
+        once,
+        merge,
+        priority,
+        after,
+        key,
+        undo,
+        replicate,
+        id,
+        version,
+        migrations,
+    })
+}
+
+pub struct SysArgs {
+    pub entity: Option<TokenStream>,
+    /// the name bound to the entity parameter, if it is a simple ident
+    pub entity_ident: Option<Ident>,
+    pub fields: Vec<TokenStream>,
+    /// the type of each field, in the same order as `fields`
+    pub field_types: Vec<TokenStream>,
+    /// `#[serde(...)]` attributes passed through from each field's fn parameter
+    pub field_attrs: Vec<TokenStream>,
+    /// doc comments on each field's fn parameter
+    pub field_docs: Vec<String>,
+    pub def_field_names: Vec<TokenStream>,
+    pub impl_field_names: Vec<TokenStream>,
+    pub args: SystemArgs,
+}
+
+pub enum SystemArgs {
+    /// Exclusive commands always have one SystemParam: &mut World
+    /// All other params are inherently inputs
+    Exclusive {
+        world: TokenStream,
+        /// the name bound to the world parameter, if it is a simple ident
+        world_ident: Option<Ident>,
+    },
+    /// System commands have multiple SystemParams
+    /// All inputs must be packed into the `In<T>` struct
+    /// eg. `In((entity, n)): In<(Entity, usize)>, mut query: Query<&mut TestUsize>`
+    System { systems_in: Vec<TokenStream> },
+}
+
+/// parse command args
+pub fn fn_args(inputs: &Punctuated<FnArg, Comma>, entity_command: bool) -> Result<SysArgs, Error> {
+    let mut exclusive_fields = Vec::<TokenStream>::new();
+    let mut exclusive_field_types = Vec::<TokenStream>::new();
+    let mut exclusive_field_attrs = Vec::<TokenStream>::new();
+    let mut exclusive_field_docs = Vec::<String>::new();
+    let mut exclusive_def_field_names = Vec::<TokenStream>::new();
+    let mut exclusive_impl_field_names = Vec::<TokenStream>::new();
+    let mut system_fields = Vec::<TokenStream>::new();
+    let mut system_field_types = Vec::<TokenStream>::new();
+    let mut system_field_attrs = Vec::<TokenStream>::new();
+    let mut system_field_docs = Vec::<String>::new();
+    let mut system_def_field_names = Vec::<TokenStream>::new();
+    let mut system_impl_field_names = Vec::<TokenStream>::new();
+    let mut systems_in = Vec::<TokenStream>::new();
+    let mut world_field = None;
+    let mut entity_field = None;
+    let mut entity_ident = None;
+    let mut world_ident = None;
+
+    for input in inputs {
+        match input {
+            // `self` types smell of methods
+            FnArg::Receiver(inner) => {
+                return Err(Error::new(inner.span(), "Commands cannot be methods"))
+            }
+            FnArg::Typed(pt) => {
+                let name = pt.pat.clone();
+                let attrs = serde_attrs(&pt.attrs);
+                let field_docs = doc_string(&pt.attrs);
+                // handle `&World`, `Entity`, and `In<>` types specially
+                // builds a list of all types in the various parts necessary for generation
+                match pt.ty.as_ref() {
+                    Type::Reference(tr) if tr.elem.to_token_stream().to_string() == "World" => {
+                        world_field = Some(quote!(#pt));
+                        world_ident = pat_ident(&pt.pat);
+                        continue;
+                    }
+                    Type::Path(path) => {
+                        if let Some(seg) = path.path.segments.last() {
+                            let ident = &seg.ident;
+                            // only the first `Entity` is the subject, any others are fields
+                            if entity_command && entity_field.is_none() && ident == "Entity" {
+                                entity_field = Some(quote!(#pt));
+                                entity_ident = pat_ident(&pt.pat);
+                                continue;
+                            } else if ident == "In" {
+                                // in this case we need to additionally parse the parameter name which may expand into more through destructuring
+                                // normally destructuring is not allowed in commands macros, but it's needed in this style to support more than one input arg
+                                // todo: support destructuring in regular command macros because I hate myself?
+
+                                let mut names = Vec::new();
+                                let Pat::TupleStruct(pat) = *pt.pat.clone() else {
+                                    return Err(Error::new(pt.span(), "Unsupported input type"));
+                                };
+                                // Parse inner names for elements of In<elem> or In<(elems,)>
+                                for pat in pat.elems {
+                                    match pat {
+                                        Pat::Ident(pat) => names.push(pat),
+                                        Pat::Tuple(pt) => {
+                                            for pat in pt.elems {
+                                                let Pat::Ident(pat) = pat else {
+                                                    return Err(Error::new(
+                                                        pat.span(),
+                                                        "Invalid path",
+                                                    ));
+                                                };
+                                                names.push(pat);
+                                            }
+                                        }
+                                        _ => return Err(Error::new(pat.span(), "Unknown input")),
+                                    }
+                                }
+
+                                // Parse inner types of In<ty> or In<(tys,)>
+                                let mut args: Vec<TokenStream> = Vec::new();
+                                match &seg.arguments {
+                                    PathArguments::AngleBracketed(inner) => {
+                                        for arg in &inner.args {
+                                            let GenericArgument::Type(ty) = arg else {
+                                                return Err(Error::new(
+                                                    arg.span(),
+                                                    "Unknown argument type",
+                                                ));
+                                            };
+
+                                            match ty {
+                                                Type::Tuple(tt) => {
+                                                    for ty in &tt.elems {
+                                                        args.push(ty.to_token_stream());
+                                                    }
+                                                }
+                                                Type::Path(tp) => args.push(tp.to_token_stream()),
+                                                _ => {
+                                                    return Err(Error::new(
+                                                        arg.span(),
+                                                        "Unsupported argument type",
+                                                    ))
+                                                }
+                                            }
+                                        }
+                                    }
+                                    _ => {
+                                        return Err(Error::new(
+                                            path.span(),
+                                            "Unsupported use of `In`",
+                                        ));
+                                    }
+                                };
+
+                                // 1:1 name:type mapping
+                                if names.len() == args.len() {
+                                    for (pat, arg) in names.into_iter().zip(args) {
+                                        let name = &pat.ident;
+                                        if entity_command
+                                            && entity_field.is_none()
+                                            && arg.to_string() == "Entity"
+                                        {
+                                            entity_ident = Some(name.clone());
+                                            entity_field = Some(quote!(#name: #arg));
+                                            systems_in.push(quote!(#name));
+                                            continue;
+                                        }
+                                        system_fields.push(quote!(#name: #arg));
+                                        system_field_types.push(arg);
+                                        system_field_attrs.push(attrs.clone());
+                                        system_field_docs.push(field_docs.clone());
+                                        system_def_field_names.push(quote!(#name));
+                                        system_impl_field_names.push(quote!(#pat));
+                                        systems_in.push(quote!(#name));
+                                    }
+                                }
+                                // 1:many name:type mapping
+                                else if names.len() == 1 && !args.is_empty() {
+                                    let pat = names.first().unwrap();
+                                    let name = &pat.ident;
+                                    system_fields.push(quote!(#name: (#(#args,)*)));
+                                    system_field_types.push(quote!((#(#args,)*)));
+                                    system_field_attrs.push(attrs);
+                                    system_field_docs.push(field_docs);
+                                    system_def_field_names.push(quote!(#name));
+                                    system_impl_field_names.push(quote!(#pat));
+                                    systems_in.push(quote!(#name));
+                                } else {
+                                    return Err(Error::new(
+                                        path.span(),
+                                        "Imbalanced names and types",
+                                    ));
+                                }
+
+                                continue;
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+
+                // these fields are not `&mut World`, `Entity`, nor `In`
+                // they only matter for exclusive systems, for normal systems these are the system parameters included by the root system
+
+                let Pat::Ident(pat) = *name.clone() else {
+                    return Err(Error::new(name.span(), "Invalid path"));
+                };
+                let name = &pat.ident;
+                let ty = &pt.ty;
+
+                exclusive_fields.push(quote!(#name: #ty));
+                exclusive_field_types.push(quote!(#ty));
+                exclusive_field_attrs.push(attrs);
+                exclusive_field_docs.push(field_docs);
+                exclusive_def_field_names.push(quote!(#name));
+                exclusive_impl_field_names.push(quote!(#pat));
+            }
+        }
+    }
+
+    // figure these out late since some parts have different meanings depending on whether this is an exclusive or normal system
+    let (fields, field_types, field_attrs, field_docs, def_field_names, impl_field_names) =
+        if world_field.is_some() {
+            (
+                exclusive_fields,
+                exclusive_field_types,
+                exclusive_field_attrs,
+                exclusive_field_docs,
+                exclusive_def_field_names,
+                exclusive_impl_field_names,
+            )
+        } else {
+            (
+                system_fields,
+                system_field_types,
+                system_field_attrs,
+                system_field_docs,
+                system_def_field_names,
+                system_impl_field_names,
+            )
+        };
+
+    let args = match world_field {
+        Some(world) => SystemArgs::Exclusive { world, world_ident },
+        None => SystemArgs::System { systems_in },
+    };
+
+    Ok(SysArgs {
+        entity: entity_field,
+        entity_ident,
+        fields,
+        field_types,
+        field_attrs,
+        field_docs,
+        def_field_names,
+        impl_field_names,
+        args,
+    })
+}
+
+/// the ident bound by a simple pattern such as `world` or `mut world`
+fn pat_ident(pat: &Pat) -> Option<Ident> {
+    match pat {
+        Pat::Ident(pat) => Some(pat.ident.clone()),
+        _ => None,
+    }
+}
+
+/// parse the return type of a function & check whether it's our special marker or not
+pub fn return_type(output: &ReturnType) -> Result<bool, Error> {
+    let ret = match &output {
+        ReturnType::Type(_, ty) => match ty.as_ref() {
+            // find optional `&mut Self` return type
+            Type::Reference(tr)
+                if tr.mutability.is_some() && tr.elem.to_token_stream().to_string() == "Self" =>
+            {
+                true
+            }
+            _ => {
+                return Err(Error::new(
+                    ty.span(),
+                    "command may not define a return type, except for `&mut Self`",
+                ))
+            }
+        },
+        _ => false,
+    };
+
+    Ok(ret)
+}
+
+/// separate out doc comments for our trait method
+pub fn docs(attrs: &[Attribute]) -> TokenStream {
+    let mut docs = Vec::new();
+    for attr in attrs {
+        if let Meta::NameValue(meta) = &attr.meta {
+            if meta.path.is_ident("doc") {
+                docs.push(attr);
+            }
+        }
+    }
+    quote!(#(#docs)*)
+}
+
+/// whether a field's type names `Entity` anywhere, eg. `Entity`, `Option<Entity>` or `(Entity, usize)`
+pub fn contains_entity(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path.path.segments.iter().any(|seg| {
+            seg.ident == "Entity"
+                || match &seg.arguments {
+                    PathArguments::AngleBracketed(args) => args.args.iter().any(|arg| match arg {
+                        GenericArgument::Type(ty) => contains_entity(ty),
+                        _ => false,
+                    }),
+                    _ => false,
+                }
+        }),
+        Type::Tuple(tuple) => tuple.elems.iter().any(contains_entity),
+        Type::Array(array) => contains_entity(&array.elem),
+        Type::Slice(slice) => contains_entity(&slice.elem),
+        Type::Paren(paren) => contains_entity(&paren.elem),
+        Type::Group(group) => contains_entity(&group.elem),
+        Type::Reference(reference) => contains_entity(&reference.elem),
+        _ => false,
+    }
+}
+
+/// collect `#[serde(...)]` attributes from a fn parameter so they can be passed to the generated field
+pub fn serde_attrs(attrs: &[Attribute]) -> TokenStream {
+    let attrs = attrs.iter().filter(|attr| attr.path().is_ident("serde"));
+    quote!(#(#attrs)*)
+}
+
+/// remove `#[serde(...)]` attributes and doc comments from fn parameters, since they are not valid on the original fn
+pub fn strip_field_attrs(inputs: &mut Punctuated<FnArg, Comma>) {
+    for input in inputs {
+        if let FnArg::Typed(pt) = input {
+            pt.attrs
+                .retain(|attr| !attr.path().is_ident("serde") && !attr.path().is_ident("doc"));
+        }
+    }
+}
+
+/// The parts of a field's `#[serde(...)]` attributes which affect its serialized form
+#[derive(Default)]
+pub struct SerdeField {
+    pub rename: Option<String>,
+    /// `Some(None)` for `#[serde(default)]`, `Some(Some(path))` for `#[serde(default = "path")]`
+    pub default: Option<Option<Path>>,
+    /// `#[serde(skip)]` or `#[serde(skip_deserializing)]`, so the field is never read
+    pub skip: bool,
+    /// `#[serde(skip_serializing)]` or `#[serde(skip_serializing_if = "path")]`, so the field may be missing
+    pub skip_serializing: bool,
+}
+
+/// parse the `rename`, `default` and `skip` options out of a field's `#[serde(...)]` attributes
+///
+/// `flatten` is rejected, since the field's properties cannot be described
+pub fn serde_field(attrs: &TokenStream) -> Result<SerdeField, Error> {
+    let mut field = SerdeField::default();
+    for attr in Attribute::parse_outer.parse2(attrs.clone())? {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                field.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("default") {
+                if meta.input.peek(Token![=]) {
+                    field.default = Some(Some(meta.value()?.parse::<LitStr>()?.parse()?));
+                } else {
+                    field.default = Some(None);
+                }
+            } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_deserializing") {
+                field.skip = true;
+            } else if meta.path.is_ident("skip_serializing")
+                || meta.path.is_ident("skip_serializing_if")
+            {
+                field.skip_serializing = true;
+                if meta.input.peek(Token![=]) {
+                    meta.value()?.parse::<Expr>()?;
+                }
+            } else if meta.path.is_ident("flatten") {
+                return Err(meta.error("`schema` does not support `#[serde(flatten)]` fields"));
+            } else if meta.input.peek(Token![=]) {
+                meta.value()?.parse::<Expr>()?;
+            } else if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+                meta.input.parse::<proc_macro2::Group>()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(field)
+}
+
+/// collect doc comments into a single string, one line per `///`
+pub fn doc_string(attrs: &[Attribute]) -> String {
+    let mut lines = Vec::new();
+    for attr in attrs {
+        if let Meta::NameValue(meta) = &attr.meta {
+            if let (
+                true,
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(lit), ..
+                }),
+            ) = (meta.path.is_ident("doc"), &meta.value)
+            {
+                let line = lit.value();
+                lines.push(line.strip_prefix(' ').unwrap_or(&line).to_string());
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+/// the largest id which JSON tooling can represent exactly, as a double
+pub const MAX_ID: u64 = (1 << 53) - 1;
+
+/// a hash which is stable between builds and compiler versions (FNV-1a), truncated to fit within [`MAX_ID`]
+pub fn stable_hash(value: &str) -> u64 {
+    let hash = value.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    hash & MAX_ID
+}
+
+/// render a type the way it would normally be written, eg. `Vec<usize>` rather than `Vec < usize >`
+pub fn type_string(ty: &TokenStream) -> String {
+    ty.to_string()
+        .replace(" < ", "<")
+        .replace("< ", "<")
+        .replace(" >", ">")
+        .replace(" :: ", "::")
+        .replace(":: ", "::")
+        .replace(" ,", ",")
+        .replace("& ", "&")
+}
+
+pub trait ExprExt {
+    fn try_to_path(&self) -> Result<Path, Error>;
+    fn try_to_ident(&self) -> Result<Ident, Error>;
+    /// a path, or a tuple of paths
+    fn try_to_paths(&self) -> Result<Vec<Path>, Error>;
+    /// an ident, or a tuple of idents
+    fn try_to_idents(&self) -> Result<Vec<Ident>, Error>;
+    fn try_to_int<N: FromStr>(&self) -> Result<N, Error>
+    where
+        N::Err: Display;
+}
+
+impl ExprExt for Expr {
+    fn try_to_int<N: FromStr>(&self) -> Result<N, Error>
+    where
+        N::Err: Display,
+    {
+        match &self {
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(lit), ..
+            }) => lit.base10_parse(),
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(lit), ..
+            }) => lit
+                .value()
+                .parse()
+                .map_err(|err| Error::new(lit.span(), err)),
+            Expr::Unary(ExprUnary {
+                op: UnOp::Neg(_),
+                expr,
+                ..
+            }) => format!("-{}", expr.try_to_int::<u64>()?)
+                .parse()
+                .map_err(|err| Error::new(self.span(), err)),
+            value => Err(Error::new(
+                value.span(),
+                format!("invalid integer: `{}`", value.to_token_stream()),
+            )),
+        }
+    }
+
+    fn try_to_path(&self) -> Result<Path, Error> {
+        let path = match &self {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(lit), ..
+            }) => lit.parse_with(Path::parse_mod_style)?,
+            Expr::Path(path) => path.path.clone(),
+            value => {
+                return Err(Error::new(
+                    value.span(),
+                    format!("invalid path: `{}`", value.to_token_stream()),
+                ))
+            }
+        };
+        Ok(path)
+    }
+
+    fn try_to_paths(&self) -> Result<Vec<Path>, Error> {
+        match &self {
+            Expr::Tuple(tuple) => tuple.elems.iter().map(Expr::try_to_path).collect(),
+            Expr::Paren(paren) => Ok(vec![paren.expr.try_to_path()?]),
+            value => Ok(vec![value.try_to_path()?]),
+        }
+    }
+
+    fn try_to_idents(&self) -> Result<Vec<Ident>, Error> {
+        match &self {
+            Expr::Tuple(tuple) => tuple.elems.iter().map(Expr::try_to_ident).collect(),
+            Expr::Paren(paren) => Ok(vec![paren.expr.try_to_ident()?]),
+            value => Ok(vec![value.try_to_ident()?]),
+        }
+    }
+
+    fn try_to_ident(&self) -> Result<Ident, Error> {
+        let ident = match &self {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(lit), ..
+            }) => lit.parse()?,
+            Expr::Path(path) => {
+                if path.path.segments.is_empty() {
+                    return Err(Error::new(path.span(), "Name must exist"));
+                }
+                if path.path.segments.len() > 1 {
+                    return Err(Error::new_spanned(
+                        path,
+                        "Name must be an ident, found path",
+                    ));
+                }
+                path.path.clone().segments.pop().unwrap().into_value().ident
+            }
+            value => {
+                return Err(Error::new(
+                    value.span(),
+                    format!("invalid name: `{}`", value.to_token_stream()),
+                ))
+            }
+        };
+        Ok(ident)
+    }
+}
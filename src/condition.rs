@@ -0,0 +1,42 @@
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::BoxedSystem;
+use bevy_utils::tracing::warn;
+use bevy_utils::HashMap;
+use std::any::TypeId;
+
+/// The `#[command(run_if = T)]` condition of each command type, initialized the first time the command is applied
+///
+/// Keeping each condition between applications lets change detection conditions, such as `resource_changed`,
+/// see what changed since the command was last applied
+#[derive(Resource, Default)]
+pub struct CommandConditions(HashMap<TypeId, BoxedSystem<(), bool>>);
+
+/// Called by generated `apply` impls, runs the condition of the command `C`
+///
+/// `condition` is only initialized the first time `C` is applied to this world
+pub fn run<C: 'static, M>(world: &mut World, condition: impl IntoSystem<(), bool, M>) -> bool {
+    let cached = world
+        .get_resource_mut::<CommandConditions>()
+        .and_then(|mut conditions| conditions.0.remove(&TypeId::of::<C>()));
+    let mut system = cached.unwrap_or_else(|| {
+        let mut system: BoxedSystem<(), bool> = Box::new(IntoSystem::into_system(condition));
+        system.initialize(world);
+        system
+    });
+
+    // our conditions are not part of any schedule, so their ticks are not otherwise kept from overflowing
+    system.check_change_tick(world.change_tick());
+    let result = system.run((), world);
+    system.apply_deferred(world);
+
+    world
+        .get_resource_or_insert_with(CommandConditions::default)
+        .0
+        .insert(TypeId::of::<C>(), system);
+    result
+}
+
+/// Called by generated `apply` impls for `on_skip = log`, when a command is skipped
+pub fn log_skipped(name: &str, reason: &str) {
+    warn!("skipped command `{name}`: {reason}");
+}
@@ -0,0 +1,390 @@
+use crate::registry::{CommandInfo, CommandKind, CommandRegistry};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::{Command, EntityCommand};
+use bevy_utils::tracing::warn;
+use std::fmt;
+use std::io::BufRead;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+
+/// A command that has been parsed and is ready to apply
+pub type DynCommand = Box<dyn FnOnce(&mut World) + Send>;
+
+/// Parses console arguments into a [`DynCommand`], stored in the `CommandRegistry`
+pub type ConsoleParseFn = fn(&mut ConsoleArgs) -> Result<DynCommand, ConsoleError>;
+
+/// Implemented for commands generated with `#[command(console)]`
+pub trait ConsoleCommand {
+    /// Builds our command from the console arguments, with each field parsed in order
+    fn from_console_args(args: &mut ConsoleArgs) -> Result<DynCommand, ConsoleError>;
+}
+
+/// Box a `Command` so that it may be applied later
+pub fn boxed_command<C: Command>(command: C) -> DynCommand {
+    Box::new(move |world: &mut World| command.apply(world))
+}
+
+/// Box an `EntityCommand` targeting `entity` so that it may be applied later
+pub fn boxed_entity_command<C: EntityCommand>(command: C, entity: Entity) -> DynCommand {
+    Box::new(move |world: &mut World| command.apply(entity, world))
+}
+
+/// A value that may be parsed from a single console argument
+pub trait ConsoleArg: Sized {
+    fn parse_arg(arg: &str) -> Result<Self, String>;
+}
+
+macro_rules! impl_console_arg {
+    ($($ty:ty),*) => {
+        $(
+            impl ConsoleArg for $ty {
+                fn parse_arg(arg: &str) -> Result<Self, String> {
+                    arg.parse().map_err(|err| format!("{err}"))
+                }
+            }
+        )*
+    };
+}
+
+impl_console_arg!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char, String
+);
+
+/// Entities may be written as their `Debug` form, eg. `12v1`, or as a bare index
+impl ConsoleArg for Entity {
+    fn parse_arg(arg: &str) -> Result<Self, String> {
+        let (index, generation) = arg.split_once('v').unwrap_or((arg, "1"));
+        let index = index.parse::<u32>().map_err(|err| format!("{err}"))?;
+        let generation = generation.parse::<u32>().map_err(|err| format!("{err}"))?;
+        Entity::try_from_bits(((generation as u64) << 32) | index as u64)
+            .map_err(|err| format!("{err}"))
+    }
+}
+
+/// `none` parses as `None`, anything else is parsed as `T`
+impl<T: ConsoleArg> ConsoleArg for Option<T> {
+    fn parse_arg(arg: &str) -> Result<Self, String> {
+        if arg == "none" {
+            Ok(None)
+        } else {
+            T::parse_arg(arg).map(Some)
+        }
+    }
+}
+
+/// The arguments of a command line, consumed in order of the command's fields
+pub struct ConsoleArgs<'a> {
+    info: &'a CommandInfo,
+    args: &'a [String],
+    next: usize,
+}
+
+impl<'a> ConsoleArgs<'a> {
+    pub fn new(info: &'a CommandInfo, args: &'a [String]) -> Self {
+        Self {
+            info,
+            args,
+            next: 0,
+        }
+    }
+
+    fn expected(&self) -> usize {
+        match self.info.kind {
+            CommandKind::Command => self.info.params.len(),
+            CommandKind::EntityCommand => self.info.params.len() + 1,
+        }
+    }
+
+    fn take<T: ConsoleArg>(&mut self, param: &'static str) -> Result<T, ConsoleError> {
+        let Some(value) = self.args.get(self.next) else {
+            return Err(ConsoleError::WrongArgCount {
+                expected: self.expected(),
+                found: self.args.len(),
+                usage: usage(self.info),
+            });
+        };
+        self.next += 1;
+
+        T::parse_arg(value).map_err(|reason| ConsoleError::InvalidArg {
+            param,
+            value: value.clone(),
+            reason,
+            usage: usage(self.info),
+        })
+    }
+
+    /// Parses the subject entity of an entity command
+    pub fn subject(&mut self) -> Result<Entity, ConsoleError> {
+        self.take("entity")
+    }
+
+    /// Parses the next field of the command
+    pub fn field<T: ConsoleArg>(&mut self) -> Result<T, ConsoleError> {
+        let index = match self.info.kind {
+            CommandKind::Command => self.next,
+            CommandKind::EntityCommand => self.next.saturating_sub(1),
+        };
+        let param = self.info.params.get(index).map_or("", |param| param.name);
+        self.take(param)
+    }
+
+    /// Ensures that every argument was consumed
+    pub fn finish(&self) -> Result<(), ConsoleError> {
+        if self.next == self.args.len() {
+            Ok(())
+        } else {
+            Err(ConsoleError::WrongArgCount {
+                expected: self.expected(),
+                found: self.args.len(),
+                usage: usage(self.info),
+            })
+        }
+    }
+}
+
+/// Describes how to invoke a command, followed by its doc comments
+///
+/// eg. `spawn_enemy <x: f32> <y: f32> <name: String>`
+pub fn usage(info: &CommandInfo) -> String {
+    let mut usage = info.name.to_string();
+    if info.kind == CommandKind::EntityCommand {
+        usage.push_str(" <entity: Entity>");
+    }
+    for param in info.params {
+        usage.push_str(&format!(" <{}: {}>", param.name, param.ty));
+    }
+    if !info.docs.is_empty() {
+        usage.push_str("\n\n");
+        usage.push_str(info.docs);
+    }
+    usage
+}
+
+/// Errors that may occur while running a command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsoleError {
+    /// The line contained no command
+    Empty,
+    /// A quoted argument was never closed
+    UnterminatedQuote,
+    /// No command is registered with this name
+    UnknownCommand(String),
+    /// The command exists, but was not generated with `#[command(console)]`
+    Unsupported(String),
+    WrongArgCount {
+        expected: usize,
+        found: usize,
+        usage: String,
+    },
+    InvalidArg {
+        param: &'static str,
+        value: String,
+        reason: String,
+        usage: String,
+    },
+}
+
+impl fmt::Display for ConsoleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConsoleError::Empty => write!(f, "no command given"),
+            ConsoleError::UnterminatedQuote => write!(f, "unterminated quote"),
+            ConsoleError::UnknownCommand(name) => write!(f, "unknown command `{name}`"),
+            ConsoleError::Unsupported(name) => {
+                write!(f, "command `{name}` cannot be run from the console")
+            }
+            ConsoleError::WrongArgCount {
+                expected,
+                found,
+                usage,
+            } => write!(
+                f,
+                "expected {expected} arguments, found {found}\nusage: {usage}"
+            ),
+            ConsoleError::InvalidArg {
+                param,
+                value,
+                reason,
+                usage,
+            } => write!(
+                f,
+                "invalid value `{value}` for `{param}`: {reason}\nusage: {usage}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConsoleError {}
+
+/// Splits a command line on whitespace, treating double-quoted sections as a single argument
+///
+/// Within quotes, `\"` and `\\` may be used to escape quotes and backslashes
+pub fn tokenize(line: &str) -> Result<Vec<String>, ConsoleError> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut token = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some(c) => token.push(c),
+                        None => return Err(ConsoleError::UnterminatedQuote),
+                    },
+                    Some(c) => token.push(c),
+                    None => return Err(ConsoleError::UnterminatedQuote),
+                }
+            }
+            tokens.push(token);
+        } else {
+            let mut token = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                token.push(c);
+                chars.next();
+            }
+            tokens.push(token);
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Parses a command line into a command, using the commands known to `registry`
+pub fn parse_command_line(
+    registry: &CommandRegistry,
+    line: &str,
+) -> Result<DynCommand, ConsoleError> {
+    let tokens = tokenize(line)?;
+    let Some((name, args)) = tokens.split_first() else {
+        return Err(ConsoleError::Empty);
+    };
+    let registration = registry
+        .get(name)
+        .ok_or_else(|| ConsoleError::UnknownCommand(name.clone()))?;
+    let parse = registration
+        .console()
+        .ok_or_else(|| ConsoleError::Unsupported(name.clone()))?;
+
+    parse(&mut ConsoleArgs::new(registration.info(), args))
+}
+
+impl CommandRegistry {
+    /// Names of console commands which start with the first word of `input`, for tab-completion
+    pub fn completions(&self, input: &str) -> Vec<&'static str> {
+        let prefix = input.trim_start();
+        if prefix.contains(char::is_whitespace) {
+            return Vec::new();
+        }
+        let mut names = self
+            .iter()
+            .filter(|registration| registration.console().is_some())
+            .map(|registration| registration.info().name)
+            .filter(|name| name.starts_with(prefix))
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+}
+
+/// Runs commands from text, eg. `world.run_command_line("spawn_enemy 10 20 \"orc\"")`
+pub trait RunCommandLineExt {
+    fn run_command_line(&mut self, line: &str) -> Result<(), ConsoleError>;
+}
+
+impl RunCommandLineExt for World {
+    fn run_command_line(&mut self, line: &str) -> Result<(), ConsoleError> {
+        let command = {
+            let registry = self.get_resource_or_insert_with(CommandRegistry::default);
+            parse_command_line(&registry, line)?
+        };
+        command(self);
+        Ok(())
+    }
+}
+
+/// Lines waiting to be run by the [`ConsolePlugin`]
+#[derive(Resource)]
+pub struct ConsoleInput {
+    sender: Sender<String>,
+    receiver: Mutex<Receiver<String>>,
+}
+
+impl Default for ConsoleInput {
+    fn default() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            sender,
+            receiver: Mutex::new(receiver),
+        }
+    }
+}
+
+impl ConsoleInput {
+    /// A handle which may be used to submit lines from any thread
+    pub fn sender(&self) -> Sender<String> {
+        self.sender.clone()
+    }
+
+    pub fn push(&self, line: impl Into<String>) {
+        // the receiver lives as long as we do
+        let _ = self.sender.send(line.into());
+    }
+}
+
+/// Runs lines submitted to [`ConsoleInput`] each frame, logging any errors as warnings
+#[derive(Default)]
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CommandRegistry>()
+            .init_resource::<ConsoleInput>()
+            .add_systems(Update, run_console_input);
+    }
+}
+
+/// Adds the [`ConsolePlugin`], reading lines from stdin on a background thread
+#[derive(Default)]
+pub struct StdinConsolePlugin;
+
+impl Plugin for StdinConsolePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<ConsolePlugin>() {
+            app.add_plugins(ConsolePlugin);
+        }
+        let sender = app.world.resource::<ConsoleInput>().sender();
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lock().lines() {
+                let Ok(line) = line else { break };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+fn run_console_input(world: &mut World) {
+    let lines = {
+        let input = world.resource::<ConsoleInput>();
+        let receiver = input.receiver.lock().unwrap();
+        receiver.try_iter().collect::<Vec<_>>()
+    };
+    for line in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Err(err) = world.run_command_line(&line) {
+            warn!("{err}");
+        }
+    }
+}
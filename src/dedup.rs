@@ -0,0 +1,234 @@
+use crate::console::DynCommand;
+use crate::flight;
+use bevy_app::{
+    App, First, Last, MainScheduleOrder, Plugin, PostStartup, PostUpdate, PreStartup, PreUpdate,
+    Startup, Update,
+};
+use bevy_ecs::prelude::*;
+use bevy_ecs::schedule::ScheduleLabel;
+use bevy_utils::synccell::SyncCell;
+use bevy_utils::HashSet;
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::panic::Location;
+
+thread_local! {
+    /// Whether stored commands are currently being applied on this thread, so that they are not stored again
+    static FLUSHING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs after each of the main schedules, ending the flush of commands applied during it
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ClearUniqueCommands;
+
+/// A command's id, and the value of its key fields
+///
+/// Keys are compared by value, their hash only decides which keys are compared
+pub struct DedupKey {
+    id: u64,
+    hash: u64,
+    value: Box<dyn KeyValue>,
+}
+
+impl DedupKey {
+    /// Called by generated `apply` impls, with a tuple of the command's key fields and entity
+    pub fn new<T: Hash + Eq + Send + Sync + 'static>(id: u64, value: T) -> Self {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        Self {
+            id,
+            hash: hasher.finish(),
+            value: Box::new(value),
+        }
+    }
+}
+
+impl PartialEq for DedupKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.hash == other.hash && self.value.eq_value(&*other.value)
+    }
+}
+
+impl Eq for DedupKey {}
+
+impl Hash for DedupKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.hash.hash(state);
+    }
+}
+
+/// Compares the type-erased values of two [`DedupKey`]s
+trait KeyValue: Send + Sync + 'static {
+    fn eq_value(&self, other: &dyn KeyValue) -> bool;
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Eq + Send + Sync + 'static> KeyValue for T {
+    fn eq_value(&self, other: &dyn KeyValue) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(self)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// A `unique = last` or `merge` command waiting to be applied at the end of the frame
+struct Pending {
+    key: DedupKey,
+    command: SyncCell<Box<dyn Any + Send>>,
+    /// downcasts `command` back into a command that may be applied
+    into_command: fn(Box<dyn Any + Send>) -> DynCommand,
+}
+
+struct Stored<C> {
+    command: C,
+    /// where the latest merged instance was issued from, for the `FlightRecorder`
+    location: Option<&'static Location<'static>>,
+    into_command: fn(C) -> DynCommand,
+}
+
+/// Tracks which `#[command(unique)]` and `#[command(key = field)]` commands have been applied,
+/// and which `#[command(unique = last)]` and `#[command(merge = T)]` commands are pending
+///
+/// `unique` commands are tracked for each flush of commands, which ends after each of the main schedules.
+/// Commands applied directly to the `World` outside of the schedules belong to the flush of the next schedule to run.
+#[derive(Resource, Default)]
+pub struct CommandDedup {
+    unique: HashSet<DedupKey>,
+    /// commands to apply at the end of the frame, in the order they were first issued
+    pending: Vec<Pending>,
+}
+
+/// Tracks which `#[command(once)]` commands have been applied, for the lifetime of the world
+///
+/// Inserted the first time a `once` command is applied, with or without the [`CommandDedupPlugin`]
+#[derive(Resource, Default)]
+pub struct OnceCommands(HashSet<DedupKey>);
+
+impl OnceCommands {
+    /// Allows every `once` command to be applied again
+    pub fn reset(&mut self) {
+        self.0.clear();
+    }
+}
+
+/// Called by generated `apply` impls, returns true the first time a `once` command is applied
+pub fn once(world: &mut World, key: DedupKey) -> bool {
+    world
+        .get_resource_or_insert_with(OnceCommands::default)
+        .0
+        .insert(key)
+}
+
+/// Called by generated `apply` impls, returns true the first time a `unique` command is applied this flush
+///
+/// Always true without a [`CommandDedup`] resource
+pub fn first(world: &mut World, key: DedupKey) -> bool {
+    match world.get_resource_mut::<CommandDedup>() {
+        Some(mut dedup) => dedup.unique.insert(key),
+        None => true,
+    }
+}
+
+/// Called by generated `apply` impls, returns true if a `unique = last` or `merge` command should be stored via [`store`] rather than applied
+///
+/// Always false without a [`CommandDedup`] resource, or while stored commands are being applied
+pub fn defer(world: &World) -> bool {
+    !flushing() && world.contains_resource::<CommandDedup>()
+}
+
+/// Whether stored commands are currently being applied on this thread
+///
+/// Prioritized commands are not buffered again meanwhile, so that commands with both `priority` and `merge` are applied
+pub(crate) fn flushing() -> bool {
+    FLUSHING.with(Cell::get)
+}
+
+/// Stores a `unique = last` or `merge` command, merging it into any instance stored this frame
+///
+/// `C` is either the command, or the command and its entity, converted via `into_command` when the frame ends
+pub fn store<C: Send + 'static>(
+    world: &mut World,
+    key: DedupKey,
+    command: C,
+    location: Option<&'static Location<'static>>,
+    merge: fn(&mut C, C),
+    into_command: fn(C) -> DynCommand,
+) {
+    let mut dedup = world.resource_mut::<CommandDedup>();
+    let stored = dedup
+        .pending
+        .iter_mut()
+        .find(|pending| pending.key == key)
+        .and_then(|pending| pending.command.get().downcast_mut::<Stored<C>>());
+    match stored {
+        Some(stored) => {
+            merge(&mut stored.command, command);
+            stored.location = location;
+        }
+        None => dedup.pending.push(Pending {
+            key,
+            command: SyncCell::new(Box::new(Stored {
+                command,
+                location,
+                into_command,
+            })),
+            into_command: |stored| {
+                let stored = stored.downcast::<Stored<C>>().unwrap();
+                flight::track(stored.location, (stored.into_command)(stored.command))
+            },
+        }),
+    }
+}
+
+/// Applies stored `unique = last` and `merge` commands at the end of each frame, and ends each flush of `unique` commands
+pub struct CommandDedupPlugin;
+
+impl Plugin for CommandDedupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CommandDedup>()
+            .add_systems(Last, flush_unique_commands)
+            .add_systems(ClearUniqueCommands, clear_unique_commands);
+
+        let mut order = app.world.resource_mut::<MainScheduleOrder>();
+        order.insert_after(First, ClearUniqueCommands);
+        order.insert_after(PreUpdate, ClearUniqueCommands);
+        order.insert_after(Update, ClearUniqueCommands);
+        order.insert_after(PostUpdate, ClearUniqueCommands);
+        order.insert_after(Last, ClearUniqueCommands);
+        order.insert_startup_after(PreStartup, ClearUniqueCommands);
+        order.insert_startup_after(Startup, ClearUniqueCommands);
+        order.insert_startup_after(PostStartup, ClearUniqueCommands);
+    }
+}
+
+/// Marks stored commands as being applied on this thread, until dropped
+struct FlushGuard(bool);
+
+impl FlushGuard {
+    fn new() -> Self {
+        Self(FLUSHING.with(|flushing| flushing.replace(true)))
+    }
+}
+
+impl Drop for FlushGuard {
+    fn drop(&mut self) {
+        FLUSHING.with(|flushing| flushing.set(self.0));
+    }
+}
+
+fn flush_unique_commands(world: &mut World) {
+    let pending = std::mem::take(&mut world.resource_mut::<CommandDedup>().pending);
+    let _flushing = FlushGuard::new();
+    for pending in pending {
+        (pending.into_command)(SyncCell::to_inner(pending.command))(world);
+    }
+}
+
+fn clear_unique_commands(mut dedup: ResMut<CommandDedup>) {
+    dedup.unique.clear();
+}
@@ -0,0 +1,442 @@
+use crate::console::DynCommand;
+use bevy_app::{App, Plugin, PreUpdate};
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::{Command, EntityCommand, EntityCommands};
+use bevy_time::Time;
+use bevy_utils::synccell::SyncCell;
+use std::mem;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const PENDING: u8 = 0;
+const APPLIED: u8 = 1;
+const CANCELLED: u8 = 2;
+
+/// When a scheduled command should be applied
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Delay {
+    /// After this much time has elapsed, according to `Time`
+    After(Duration),
+    /// Once the [`CommandScheduler`] has reached this tick
+    AtTick(u64),
+}
+
+/// Cancels a scheduled command, returned by generated `foo_after` and `foo_at_tick` methods, and by [`RepeatExt::repeat`]
+///
+/// Dropping the token does not cancel the command
+#[derive(Clone, Debug, Default)]
+pub struct CommandToken {
+    state: Arc<AtomicU8>,
+}
+
+impl CommandToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prevents the command from being applied, if it has not been already
+    pub fn cancel(&self) {
+        let _ =
+            self.state
+                .compare_exchange(PENDING, CANCELLED, Ordering::AcqRel, Ordering::Acquire);
+    }
+
+    /// Whether the command was cancelled, including entity commands whose entity was despawned
+    pub fn is_cancelled(&self) -> bool {
+        self.state.load(Ordering::Acquire) == CANCELLED
+    }
+
+    /// Whether the command was applied, or a repeating command has finished repeating
+    pub fn is_applied(&self) -> bool {
+        self.state.load(Ordering::Acquire) == APPLIED
+    }
+
+    /// Whether the command has been either applied or cancelled
+    pub fn is_finished(&self) -> bool {
+        self.state.load(Ordering::Acquire) != PENDING
+    }
+
+    /// Marks the command as applied, returning false if it was cancelled
+    fn apply(&self) -> bool {
+        self.state
+            .compare_exchange(PENDING, APPLIED, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+}
+
+enum Due {
+    Elapsed(Duration),
+    Tick(u64),
+}
+
+struct ScheduledEntry {
+    due: Due,
+    token: CommandToken,
+    /// Entity commands are cancelled if their entity no longer exists
+    entity: Option<Entity>,
+    command: SyncCell<DynCommand>,
+}
+
+type ConditionFn = Box<dyn Fn(&World) -> bool + Send + Sync>;
+type RepeatedCommand = Box<dyn FnMut(&mut World) + Send>;
+
+/// How often a repeating command is applied, see [`RepeatExt::repeat`]
+///
+/// eg. `Repeat::every(Duration::from_secs_f32(0.5)).times(10)`
+pub struct Repeat {
+    interval: Duration,
+    times: Option<u32>,
+    condition: Option<ConditionFn>,
+}
+
+impl Repeat {
+    /// Applies the command each time `interval` elapses, until cancelled
+    ///
+    /// A zero interval applies the command once per frame
+    pub fn every(interval: Duration) -> Self {
+        Self {
+            interval,
+            times: None,
+            condition: None,
+        }
+    }
+
+    /// Stops after the command has been applied `times` times
+    pub fn times(mut self, times: u32) -> Self {
+        self.times = Some(times);
+        self
+    }
+
+    /// Stops once `condition` returns false, checked before each application
+    pub fn run_while(mut self, condition: impl Fn(&World) -> bool + Send + Sync + 'static) -> Self {
+        self.condition = Some(Box::new(condition));
+        self
+    }
+}
+
+struct RepeatingEntry {
+    interval: Duration,
+    due: Duration,
+    remaining: Option<u32>,
+    condition: Option<SyncCell<ConditionFn>>,
+    token: CommandToken,
+    entity: Option<Entity>,
+    command: SyncCell<RepeatedCommand>,
+}
+
+impl RepeatingEntry {
+    /// Applies our command as many times as it has become due, returning false once we've finished
+    fn run(&mut self, world: &mut World, elapsed: Duration) -> bool {
+        while self.due <= elapsed {
+            if self.token.is_cancelled()
+                || self
+                    .entity
+                    .is_some_and(|entity| world.get_entity(entity).is_none())
+            {
+                self.token.cancel();
+                return false;
+            }
+            if self.remaining == Some(0)
+                || self
+                    .condition
+                    .as_mut()
+                    .is_some_and(|condition| !(condition.get())(world))
+            {
+                self.token.apply();
+                return false;
+            }
+
+            (self.command.get())(world);
+            if let Some(remaining) = &mut self.remaining {
+                *remaining -= 1;
+            }
+            self.due += self.interval;
+            if self.interval.is_zero() {
+                break;
+            }
+        }
+        if self.remaining == Some(0) {
+            self.token.apply();
+            return false;
+        }
+        !self.token.is_cancelled()
+    }
+}
+
+/// Commands waiting to be applied later, see [`CommandSchedulerPlugin`]
+#[derive(Resource, Default)]
+pub struct CommandScheduler {
+    tick: u64,
+    entries: Vec<ScheduledEntry>,
+    repeating: Vec<RepeatingEntry>,
+}
+
+impl CommandScheduler {
+    /// The number of frames the scheduler has run for, used by [`Delay::AtTick`]
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// The number of commands waiting to be applied, including repeating commands
+    pub fn len(&self) -> usize {
+        self.entries.len() + self.repeating.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.repeating.is_empty()
+    }
+
+    /// Cancels every scheduled and repeating command
+    pub fn clear(&mut self) {
+        for entry in self.entries.drain(..) {
+            entry.token.cancel();
+        }
+        for entry in self.repeating.drain(..) {
+            entry.token.cancel();
+        }
+    }
+}
+
+/// Schedules `command` to be applied later
+pub fn schedule(
+    world: &mut World,
+    delay: Delay,
+    token: CommandToken,
+    entity: Option<Entity>,
+    command: DynCommand,
+) {
+    let due = match delay {
+        Delay::After(delay) => Due::Elapsed(elapsed(world) + delay),
+        Delay::AtTick(tick) => Due::Tick(tick),
+    };
+    world
+        .get_resource_or_insert_with(CommandScheduler::default)
+        .entries
+        .push(ScheduledEntry {
+            due,
+            token,
+            entity,
+            command: SyncCell::new(command),
+        });
+}
+
+fn elapsed(world: &World) -> Duration {
+    world
+        .get_resource::<Time>()
+        .map_or(Duration::ZERO, |time| time.elapsed())
+}
+
+/// Schedules `command` to be applied repeatedly, the first time once `repeat`'s interval has elapsed
+pub fn schedule_repeating(
+    world: &mut World,
+    repeat: Repeat,
+    token: CommandToken,
+    entity: Option<Entity>,
+    command: RepeatedCommand,
+) {
+    let due = elapsed(world) + repeat.interval;
+    world
+        .get_resource_or_insert_with(CommandScheduler::default)
+        .repeating
+        .push(RepeatingEntry {
+            interval: repeat.interval,
+            due,
+            remaining: repeat.times,
+            condition: repeat.condition.map(SyncCell::new),
+            token,
+            entity,
+            command: SyncCell::new(command),
+        });
+}
+
+/// Schedules a command when applied, used by generated `foo_after` and `foo_at_tick` methods
+pub struct Scheduled<C> {
+    delay: Delay,
+    token: CommandToken,
+    command: C,
+}
+
+impl<C> Scheduled<C> {
+    pub fn new(delay: Delay, token: CommandToken, command: C) -> Self {
+        Self {
+            delay,
+            token,
+            command,
+        }
+    }
+}
+
+impl<C: Command> Command for Scheduled<C> {
+    fn apply(self, world: &mut World) {
+        let command = self.command;
+        schedule(
+            world,
+            self.delay,
+            self.token,
+            None,
+            Box::new(move |world: &mut World| command.apply(world)),
+        );
+    }
+}
+
+impl<C: EntityCommand> EntityCommand for Scheduled<C> {
+    fn apply(self, id: Entity, world: &mut World) {
+        let command = self.command;
+        schedule(
+            world,
+            self.delay,
+            self.token,
+            Some(id),
+            Box::new(move |world: &mut World| command.apply(id, world)),
+        );
+    }
+}
+
+/// Applies a cloneable command repeatedly, stopped via the returned [`CommandToken`]
+///
+/// eg. `commands.entity(player).repeat(Repeat::every(Duration::from_secs_f32(0.5)), RegenEntityCommand { hp: 1 })`
+///
+/// Commands must be generated with `#[command(clone)]`, and are applied by the [`CommandSchedulerPlugin`]
+pub trait RepeatExt<C> {
+    fn repeat(&mut self, repeat: Repeat, command: C) -> CommandToken;
+}
+
+impl<C: Command + Clone> RepeatExt<C> for World {
+    fn repeat(&mut self, repeat: Repeat, command: C) -> CommandToken {
+        let token = CommandToken::new();
+        schedule_repeating(
+            self,
+            repeat,
+            token.clone(),
+            None,
+            Box::new(move |world: &mut World| command.clone().apply(world)),
+        );
+        token
+    }
+}
+
+impl<C: Command + Clone> RepeatExt<C> for Commands<'_, '_> {
+    fn repeat(&mut self, repeat: Repeat, command: C) -> CommandToken {
+        let token = CommandToken::new();
+        let scheduled = token.clone();
+        self.add(move |world: &mut World| {
+            schedule_repeating(
+                world,
+                repeat,
+                scheduled,
+                None,
+                Box::new(move |world: &mut World| command.clone().apply(world)),
+            );
+        });
+        token
+    }
+}
+
+impl<C: EntityCommand + Clone> RepeatExt<C> for EntityWorldMut<'_> {
+    fn repeat(&mut self, repeat: Repeat, command: C) -> CommandToken {
+        let token = CommandToken::new();
+        let id = self.id();
+        self.world_scope(|world| {
+            schedule_repeating(
+                world,
+                repeat,
+                token.clone(),
+                Some(id),
+                Box::new(move |world: &mut World| command.clone().apply(id, world)),
+            );
+        });
+        token
+    }
+}
+
+impl<C: EntityCommand + Clone> RepeatExt<C> for EntityCommands<'_> {
+    fn repeat(&mut self, repeat: Repeat, command: C) -> CommandToken {
+        let token = CommandToken::new();
+        let scheduled = token.clone();
+        self.add(move |id: Entity, world: &mut World| {
+            schedule_repeating(
+                world,
+                repeat,
+                scheduled,
+                Some(id),
+                Box::new(move |world: &mut World| command.clone().apply(id, world)),
+            );
+        });
+        token
+    }
+}
+
+/// Applies scheduled commands once they are due, in `PreUpdate`
+///
+/// Requires bevy's `TimePlugin` for [`Delay::After`]
+pub struct CommandSchedulerPlugin;
+
+impl Plugin for CommandSchedulerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CommandScheduler>()
+            .add_systems(PreUpdate, apply_scheduled_commands);
+    }
+}
+
+fn apply_scheduled_commands(world: &mut World) {
+    let elapsed = elapsed(world);
+
+    let due = {
+        let mut scheduler = world.resource_mut::<CommandScheduler>();
+        scheduler.tick += 1;
+        let tick = scheduler.tick;
+
+        let mut due = Vec::new();
+        let mut i = 0;
+        while i < scheduler.entries.len() {
+            let entry = &scheduler.entries[i];
+            let is_due = match entry.due {
+                Due::Elapsed(at) => at <= elapsed,
+                Due::Tick(at) => at <= tick,
+            };
+            if is_due || entry.token.is_cancelled() {
+                due.push(scheduler.entries.remove(i));
+            } else {
+                i += 1;
+            }
+        }
+        due
+    };
+
+    for entry in due {
+        if entry
+            .entity
+            .is_some_and(|entity| world.get_entity(entity).is_none())
+        {
+            entry.token.cancel();
+        }
+        if entry.token.apply() {
+            SyncCell::to_inner(entry.command)(world);
+        }
+    }
+
+    // taken while applied, since our commands may repeat further commands
+    let mut repeating = mem::take(&mut world.resource_mut::<CommandScheduler>().repeating);
+    repeating.retain_mut(|entry| entry.run(world, elapsed));
+
+    // drop entity commands whose entity has since been despawned
+    world.resource_scope(|world, mut scheduler: Mut<CommandScheduler>| {
+        repeating.append(&mut scheduler.repeating);
+        scheduler.repeating = repeating;
+        scheduler.entries.retain(|entry| match entry.entity {
+            Some(entity) if world.get_entity(entity).is_none() => {
+                entry.token.cancel();
+                false
+            }
+            _ => true,
+        });
+        scheduler.repeating.retain(|entry| match entry.entity {
+            Some(entity) if world.get_entity(entity).is_none() => {
+                entry.token.cancel();
+                false
+            }
+            _ => !entry.token.is_cancelled(),
+        });
+    });
+}
@@ -0,0 +1,33 @@
+use bevy_app::{App, PostUpdate};
+use bevy_ecs::event::{Events, ManualEventReader};
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::Command;
+
+/// Applies commands sent as events, for commands generated with `#[command(event)]`
+pub trait AddCommandEventExt {
+    /// Adds `Events<C>`, and reads them in `PostUpdate` applying a clone of each command in the order they were sent
+    ///
+    /// The events are not drained, so other systems may still read the commands as regular events, before or after they are applied.
+    /// Commands sent after `PostUpdate` are applied in the next frame's `PostUpdate`
+    fn add_command_event<C: Command + Event + Clone>(&mut self) -> &mut Self;
+}
+
+impl AddCommandEventExt for App {
+    fn add_command_event<C: Command + Event + Clone>(&mut self) -> &mut Self {
+        self.add_event::<C>()
+            .add_systems(PostUpdate, apply_command_events::<C>)
+    }
+}
+
+fn apply_command_events<C: Command + Event + Clone>(
+    world: &mut World,
+    mut reader: Local<ManualEventReader<C>>,
+) {
+    let commands = reader
+        .read(world.resource::<Events<C>>())
+        .cloned()
+        .collect::<Vec<_>>();
+    for command in commands {
+        command.apply(world);
+    }
+}
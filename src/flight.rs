@@ -0,0 +1,243 @@
+use crate::console::DynCommand;
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::{Command, EntityCommand};
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::fmt::{self, Debug, Display};
+use std::io::{self, Write};
+use std::panic::Location;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, Once};
+
+/// Whether commands are currently being recorded, checked before taking the lock
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static RECORDS: Mutex<Records> = Mutex::new(Records {
+    capacity: 0,
+    records: VecDeque::new(),
+});
+
+thread_local! {
+    /// Where the command about to be applied on this thread was issued from
+    static CALLER: Cell<Option<&'static Location<'static>>> = const { Cell::new(None) };
+}
+
+struct Records {
+    capacity: usize,
+    records: VecDeque<FlightRecord>,
+}
+
+/// A single command applied while the [`FlightRecorder`] was enabled
+#[derive(Clone, Debug)]
+pub struct FlightRecord {
+    /// The command's name, see `CommandInfo::name`
+    pub name: &'static str,
+    /// The entity an entity command was applied to
+    pub entity: Option<Entity>,
+    /// The command's `Debug` output, for commands generated with `#[command(debug)]`
+    pub args: Option<String>,
+    /// Where the command was issued from, if it was issued through its generated trait method
+    pub location: Option<&'static Location<'static>>,
+}
+
+impl Display for FlightRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)?;
+        if let Some(entity) = self.entity {
+            write!(f, "({entity:?})")?;
+        }
+        if let Some(args) = &self.args {
+            write!(f, " {args}")?;
+        }
+        if let Some(location) = self.location {
+            write!(f, " at {location}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A ring buffer of the last few commands applied, across every world
+///
+/// Disabled by default, see [`FlightRecorderPlugin`] to enable it and dump its records when a panic occurs
+pub struct FlightRecorder;
+
+impl FlightRecorder {
+    /// Starts recording the last `capacity` commands applied
+    pub fn enable(capacity: usize) {
+        let mut records = lock();
+        records.capacity = capacity;
+        while records.records.len() > capacity {
+            records.records.pop_front();
+        }
+        ENABLED.store(capacity > 0, Ordering::Release);
+    }
+
+    /// Stops recording, keeping any existing records
+    pub fn disable() {
+        ENABLED.store(false, Ordering::Release);
+    }
+
+    pub fn is_enabled() -> bool {
+        ENABLED.load(Ordering::Acquire)
+    }
+
+    /// The recorded commands, oldest first
+    pub fn records() -> Vec<FlightRecord> {
+        lock().records.iter().cloned().collect()
+    }
+
+    pub fn clear() {
+        lock().records.clear();
+    }
+
+    /// Writes our records in a human readable form, oldest first
+    pub fn dump(writer: &mut impl Write) -> io::Result<()> {
+        let records = FlightRecorder::records();
+        writeln!(
+            writer,
+            "last {} commands applied (oldest first):",
+            records.len()
+        )?;
+        for (i, record) in records.iter().enumerate() {
+            writeln!(writer, "{i:>4}: {record}")?;
+        }
+        Ok(())
+    }
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Records> {
+    // a panic while holding the lock cannot leave our records in an invalid state
+    RECORDS.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Called at the start of generated `apply` impls, takes where the command being applied was issued from
+///
+/// Taken before any of the command's conditions are checked, as those may apply commands of their own
+pub fn caller() -> Option<&'static Location<'static>> {
+    CALLER.with(Cell::take)
+}
+
+/// Wraps a buffered command, so that it is recorded with the location it was originally issued from
+pub fn track(location: Option<&'static Location<'static>>, command: DynCommand) -> DynCommand {
+    match location {
+        Some(location) => Box::new(move |world: &mut World| {
+            CALLER.with(|caller| caller.set(Some(location)));
+            command(world);
+        }),
+        None => command,
+    }
+}
+
+/// Called by generated `apply` impls to record the command being applied, once it is known that it will be applied
+pub fn record(
+    name: &'static str,
+    entity: Option<Entity>,
+    args: Option<&dyn Debug>,
+    location: Option<&'static Location<'static>>,
+) {
+    if !FlightRecorder::is_enabled() {
+        return;
+    }
+
+    let record = FlightRecord {
+        name,
+        entity,
+        args: args.map(|args| format!("{args:?}")),
+        location,
+    };
+    let mut records = lock();
+    if records.capacity == 0 {
+        return;
+    }
+    while records.records.len() >= records.capacity {
+        records.records.pop_front();
+    }
+    records.records.push_back(record);
+}
+
+/// Wraps a command with the location it was issued from, used by generated trait methods
+pub struct Tracked<C> {
+    command: C,
+    location: &'static Location<'static>,
+}
+
+impl<C> Tracked<C> {
+    #[track_caller]
+    pub fn new(command: C) -> Self {
+        Self {
+            command,
+            location: Location::caller(),
+        }
+    }
+}
+
+impl<C: Command> Command for Tracked<C> {
+    fn apply(self, world: &mut World) {
+        CALLER.with(|caller| caller.set(Some(self.location)));
+        self.command.apply(world);
+    }
+}
+
+impl<C: EntityCommand> EntityCommand for Tracked<C> {
+    fn apply(self, id: Entity, world: &mut World) {
+        CALLER.with(|caller| caller.set(Some(self.location)));
+        self.command.apply(id, world);
+    }
+}
+
+/// Enables the [`FlightRecorder`], and dumps its records to stderr when a panic occurs
+///
+/// eg. `FlightRecorderPlugin::new(128).with_file("crash_commands.txt")`
+pub struct FlightRecorderPlugin {
+    capacity: usize,
+    path: Option<PathBuf>,
+}
+
+impl Default for FlightRecorderPlugin {
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
+
+impl FlightRecorderPlugin {
+    /// Records the last `capacity` commands applied
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            path: None,
+        }
+    }
+
+    /// Also writes the records to a file when a panic occurs
+    pub fn with_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+}
+
+impl Plugin for FlightRecorderPlugin {
+    fn build(&self, _app: &mut App) {
+        FlightRecorder::enable(self.capacity);
+        install_panic_hook(self.path.clone());
+    }
+}
+
+/// Chains onto the existing panic hook, only the first call has any effect
+fn install_panic_hook(path: Option<PathBuf>) {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = FlightRecorder::dump(&mut io::stderr().lock());
+            if let Some(path) = &path {
+                if let Ok(mut file) = std::fs::File::create(path) {
+                    let _ = writeln!(file, "{info}");
+                    let _ = FlightRecorder::dump(&mut file);
+                }
+            }
+            hook(info);
+        }));
+    });
+}
@@ -0,0 +1,234 @@
+#[cfg(feature = "serde")]
+use crate::json::{CommandRequest, RunJsonCommandExt};
+use bevy_app::{App, PreUpdate};
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::{Command, EntityCommand};
+use bevy_input::{ButtonInput, InputSystem};
+#[cfg(feature = "serde")]
+use bevy_utils::tracing::warn;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::hash::Hash;
+
+/// Queues a bound command
+type BoundCommand = Box<dyn Fn(&mut Commands) + Send + Sync>;
+
+/// When a bound command is issued
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum InputTrigger {
+    /// The frame the input is pressed
+    #[default]
+    JustPressed,
+    /// Every frame the input is held
+    Pressed,
+    /// The frame the input is released
+    JustReleased,
+}
+
+impl InputTrigger {
+    fn is_triggered<T: Copy + Eq + Hash + Send + Sync>(
+        self,
+        input: &ButtonInput<T>,
+        button: T,
+    ) -> bool {
+        match self {
+            InputTrigger::JustPressed => input.just_pressed(button),
+            InputTrigger::Pressed => input.pressed(button),
+            InputTrigger::JustReleased => input.just_released(button),
+        }
+    }
+}
+
+struct Binding<T> {
+    input: T,
+    trigger: InputTrigger,
+    command: BoundCommand,
+}
+
+/// Commands bound to inputs read from `ButtonInput<T>`, eg. `KeyCode`, `MouseButton` or `GamepadButton`
+///
+/// See [`BindCommandExt`] to add bindings
+#[derive(Resource)]
+pub struct CommandBindings<T> {
+    bindings: Vec<Binding<T>>,
+}
+
+impl<T> Default for CommandBindings<T> {
+    fn default() -> Self {
+        Self {
+            bindings: Vec::new(),
+        }
+    }
+}
+
+impl<T: Copy + Eq + Hash + Send + Sync + 'static> CommandBindings<T> {
+    /// Queues a clone of `command` each time `input` is triggered
+    pub fn bind(&mut self, input: T, trigger: InputTrigger, command: impl Command + Clone + Sync) {
+        self.bindings.push(Binding {
+            input,
+            trigger,
+            command: Box::new(move |commands| commands.add(command.clone())),
+        });
+    }
+
+    /// Queues a clone of `command` for `entity` each time `input` is triggered, unless `entity` has been despawned
+    pub fn bind_entity(
+        &mut self,
+        input: T,
+        trigger: InputTrigger,
+        entity: Entity,
+        command: impl EntityCommand + Clone + Sync,
+    ) {
+        self.bindings.push(Binding {
+            input,
+            trigger,
+            command: Box::new(move |commands| {
+                if let Some(mut entity) = commands.get_entity(entity) {
+                    entity.add(command.clone());
+                }
+            }),
+        });
+    }
+
+    /// Runs a serialized command each time `input` is triggered, see `RunJsonCommandExt`
+    #[cfg(feature = "serde")]
+    pub fn bind_request(&mut self, input: T, trigger: InputTrigger, request: CommandRequest) {
+        self.bindings.push(Binding {
+            input,
+            trigger,
+            command: Box::new(move |commands| {
+                let request = request.clone();
+                commands.add(move |world: &mut World| {
+                    let name = request.command.clone();
+                    if let Err(err) = world.run_json_command(request) {
+                        warn!("failed to run bound command `{name}`: {err}");
+                    }
+                });
+            }),
+        });
+    }
+
+    /// Removes every binding for `input`
+    pub fn unbind(&mut self, input: T) {
+        self.bindings.retain(|binding| binding.input != input);
+    }
+
+    pub fn len(&self) -> usize {
+        self.bindings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+}
+
+/// A serialized binding, eg. loaded from a config file
+///
+/// eg. `{ "input": "KeyF", "command": "fire", "args": { "power": 3 } }`
+///
+/// Commands are found via the `CommandRegistry`, so must be generated with `#[command(serde)]` and registered
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct InputBinding<T> {
+    pub input: T,
+    #[serde(default)]
+    pub trigger: InputTrigger,
+    #[serde(flatten)]
+    pub request: CommandRequest,
+}
+
+/// Binds commands to inputs, issued in `PreUpdate` after bevy reads its inputs
+pub trait BindCommandExt {
+    /// Queues a clone of `command` the frame `input` is pressed
+    ///
+    /// eg. `app.bind_command(KeyCode::KeyF, FireCommand { power: 3 })`
+    fn bind_command<T: Copy + Eq + Hash + Send + Sync + 'static>(
+        &mut self,
+        input: T,
+        command: impl Command + Clone + Sync,
+    ) -> &mut Self {
+        self.bind_command_with(input, InputTrigger::JustPressed, command)
+    }
+
+    fn bind_command_with<T: Copy + Eq + Hash + Send + Sync + 'static>(
+        &mut self,
+        input: T,
+        trigger: InputTrigger,
+        command: impl Command + Clone + Sync,
+    ) -> &mut Self;
+
+    /// Queues a clone of `command` for `entity` the frame `input` is pressed
+    fn bind_entity_command<T: Copy + Eq + Hash + Send + Sync + 'static>(
+        &mut self,
+        input: T,
+        entity: Entity,
+        command: impl EntityCommand + Clone + Sync,
+    ) -> &mut Self;
+
+    /// Adds each serialized binding, see [`InputBinding`]
+    #[cfg(feature = "serde")]
+    fn bind_commands<T: Copy + Eq + Hash + Send + Sync + 'static>(
+        &mut self,
+        bindings: impl IntoIterator<Item = InputBinding<T>>,
+    ) -> &mut Self;
+}
+
+impl BindCommandExt for App {
+    fn bind_command_with<T: Copy + Eq + Hash + Send + Sync + 'static>(
+        &mut self,
+        input: T,
+        trigger: InputTrigger,
+        command: impl Command + Clone + Sync,
+    ) -> &mut Self {
+        bindings::<T>(self).bind(input, trigger, command);
+        self
+    }
+
+    fn bind_entity_command<T: Copy + Eq + Hash + Send + Sync + 'static>(
+        &mut self,
+        input: T,
+        entity: Entity,
+        command: impl EntityCommand + Clone + Sync,
+    ) -> &mut Self {
+        bindings::<T>(self).bind_entity(input, InputTrigger::JustPressed, entity, command);
+        self
+    }
+
+    #[cfg(feature = "serde")]
+    fn bind_commands<T: Copy + Eq + Hash + Send + Sync + 'static>(
+        &mut self,
+        bindings: impl IntoIterator<Item = InputBinding<T>>,
+    ) -> &mut Self {
+        let mut command_bindings = self::bindings::<T>(self);
+        for binding in bindings {
+            command_bindings.bind_request(binding.input, binding.trigger, binding.request);
+        }
+        self
+    }
+}
+
+/// Our bindings for `T`, adding the system issuing them the first time
+fn bindings<T: Copy + Eq + Hash + Send + Sync + 'static>(
+    app: &mut App,
+) -> Mut<'_, CommandBindings<T>> {
+    if !app.world.contains_resource::<CommandBindings<T>>() {
+        app.init_resource::<CommandBindings<T>>()
+            .init_resource::<ButtonInput<T>>()
+            .add_systems(PreUpdate, issue_bound_commands::<T>.after(InputSystem));
+    }
+    app.world.resource_mut::<CommandBindings<T>>()
+}
+
+fn issue_bound_commands<T: Copy + Eq + Hash + Send + Sync + 'static>(
+    input: Res<ButtonInput<T>>,
+    bindings: Res<CommandBindings<T>>,
+    mut commands: Commands,
+) {
+    for binding in &bindings.bindings {
+        if binding.trigger.is_triggered(&input, binding.input) {
+            (binding.command)(&mut commands);
+        }
+    }
+}
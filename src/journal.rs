@@ -0,0 +1,193 @@
+use crate::json::{CommandRequest, RunJsonCommandExt};
+use crate::registry::{CommandMeta, CommandRegistry};
+use bevy_app::{App, First, Last, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_utils::tracing::{error, warn};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+thread_local! {
+    /// How many journaled commands are currently being applied on this thread
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A single command recorded by the [`CommandJournal`]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JournalEntry {
+    /// The [`CommandJournal::tick`] the command was applied on
+    pub tick: u64,
+    #[serde(flatten)]
+    pub request: CommandRequest,
+}
+
+/// Records every `#[command(serde)]` command as it is applied, so that it may be replayed later
+///
+/// Commands applied from within another command are not recorded, since replaying the outer command will issue them again
+#[derive(Resource, Default, Debug)]
+pub struct CommandJournal {
+    entries: Vec<JournalEntry>,
+    tick: u64,
+    paused: bool,
+}
+
+impl CommandJournal {
+    /// The current tick, advanced at the end of every frame by the [`CommandJournalPlugin`]
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    pub fn take_entries(&mut self) -> Vec<JournalEntry> {
+        std::mem::take(&mut self.entries)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Stops recording commands until [`CommandJournal::resume`] is called
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Writes our entries to a file, one JSON object per line
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(std::fs::File::create(path)?);
+        for entry in &self.entries {
+            serde_json::to_writer(&mut writer, entry)?;
+            writeln!(writer)?;
+        }
+        writer.flush()
+    }
+
+    /// Reads entries previously written by [`CommandJournal::save`]
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Vec<JournalEntry>> {
+        let reader = BufReader::new(std::fs::File::open(path)?);
+        let mut entries = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            entries.push(serde_json::from_str(&line)?);
+        }
+        Ok(entries)
+    }
+}
+
+/// Marks a journaled command as being applied, until dropped
+pub struct JournalGuard(());
+
+impl Drop for JournalGuard {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// Called by generated `apply` impls to record the command being applied
+pub fn record<T: CommandMeta + Serialize>(
+    world: &mut World,
+    command: &T,
+    entity: Option<Entity>,
+) -> JournalGuard {
+    let depth = DEPTH.with(|depth| depth.replace(depth.get() + 1));
+    let guard = JournalGuard(());
+    if depth == 0 {
+        if let Some(mut journal) = world.get_resource_mut::<CommandJournal>() {
+            if !journal.paused {
+                match CommandRequest::new(command, entity) {
+                    Ok(request) => {
+                        let tick = journal.tick;
+                        journal.entries.push(JournalEntry { tick, request });
+                    }
+                    Err(err) => error!("failed to journal `{}`: {err}", T::INFO.name),
+                }
+            }
+        }
+    }
+    guard
+}
+
+/// Entries waiting to be replayed by the [`CommandJournalPlugin`]
+///
+/// Each entry is applied at the start of the frame matching its tick, and must be registered in the `CommandRegistry`
+#[derive(Resource, Default, Debug)]
+pub struct JournalReplay {
+    entries: VecDeque<JournalEntry>,
+}
+
+impl JournalReplay {
+    pub fn new(mut entries: Vec<JournalEntry>) -> Self {
+        entries.sort_by_key(|entry| entry.tick);
+        Self {
+            entries: entries.into(),
+        }
+    }
+
+    /// Whether every entry has been replayed
+    pub fn is_finished(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Advances the [`CommandJournal`] tick each frame, and replays any [`JournalReplay`] entries
+///
+/// Commands are recorded into the [`CommandJournal`] resource
+#[derive(Default)]
+pub struct CommandJournalPlugin {
+    replay: Vec<JournalEntry>,
+}
+
+impl CommandJournalPlugin {
+    /// Replays `entries` at the same ticks they were recorded on
+    pub fn replay(entries: Vec<JournalEntry>) -> Self {
+        Self { replay: entries }
+    }
+}
+
+impl Plugin for CommandJournalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CommandRegistry>()
+            .init_resource::<CommandJournal>()
+            .insert_resource(JournalReplay::new(self.replay.clone()))
+            .add_systems(First, replay_journal)
+            .add_systems(Last, advance_journal_tick);
+    }
+}
+
+fn advance_journal_tick(mut journal: ResMut<CommandJournal>) {
+    journal.tick += 1;
+}
+
+fn replay_journal(world: &mut World) {
+    let tick = world.resource::<CommandJournal>().tick;
+    loop {
+        let entry = {
+            let mut replay = world.resource_mut::<JournalReplay>();
+            match replay.entries.front() {
+                Some(entry) if entry.tick <= tick => replay.entries.pop_front().unwrap(),
+                _ => break,
+            }
+        };
+        if let Err(err) = world.run_json_command(entry.request) {
+            warn!(
+                "failed to replay journal entry at tick {}: {err}",
+                entry.tick
+            );
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use crate::console::{ConsoleArg, DynCommand};
+use crate::registry::{CommandKind, CommandMeta, CommandRegistration, CommandRegistry};
+use bevy_ecs::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+pub use serde_json::Value;
+use std::fmt;
+
+/// Deserializes a command from its JSON args, stored in the `CommandRegistry`
+pub type JsonParseFn = fn(Option<Entity>, Value) -> Result<DynCommand, JsonError>;
+
+/// Upgrades args serialized by an older version of a command, stored in the `CommandRegistry`
+pub type MigrateFn = fn(u32, Value) -> Result<Value, JsonError>;
+
+/// Implemented for commands generated with `#[command(serde)]`
+pub trait JsonCommand {
+    /// Builds our command from its serialized fields
+    ///
+    /// Entity commands additionally require the entity they apply to
+    fn from_json(entity: Option<Entity>, args: Value) -> Result<DynCommand, JsonError>;
+
+    /// Upgrades args serialized at `version` to our current layout, via `#[command(migrate_from(...))]`
+    fn migrate(version: u32, args: Value) -> Result<Value, JsonError>;
+}
+
+/// A command and its arguments in serialized form
+///
+/// eg. `{ "command": "heal", "entity": "12v1", "args": { "amount": 5 } }`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CommandRequest {
+    /// The method name of the command
+    pub command: String,
+    /// The subject of an entity command, either as `Entity::to_bits` or its `Debug` form
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_entity",
+        deserialize_with = "deserialize_entity"
+    )]
+    pub entity: Option<Entity>,
+    /// The fields of the command struct
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub args: Value,
+    /// The command's stable id, which takes priority over its name when present
+    ///
+    /// Serialized as `command_id`, leaving `id` free for protocols such as the [`RemoteRequest`](crate::remote::RemoteRequest)'s
+    #[serde(
+        rename = "command_id",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub id: Option<u64>,
+    /// The version the args were serialized with, assumed to be current when missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+}
+
+impl CommandRequest {
+    /// Serializes `command` into a request, targeting `entity` if it is an entity command
+    pub fn new<T: CommandMeta + Serialize>(
+        command: &T,
+        entity: Option<Entity>,
+    ) -> Result<Self, JsonError> {
+        Ok(Self {
+            command: T::INFO.name.to_string(),
+            entity,
+            args: serde_json::to_value(command).map_err(JsonError::Args)?,
+            id: Some(T::INFO.id),
+            version: Some(T::INFO.version),
+        })
+    }
+}
+
+fn serialize_entity<S: Serializer>(
+    entity: &Option<Entity>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match entity {
+        Some(entity) => serializer.serialize_u64(entity.to_bits()),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn deserialize_entity<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Entity>, D::Error> {
+    use serde::de::Error;
+    let value = Option::<Value>::deserialize(deserializer)?;
+    value
+        .map(|value| entity_from_json(&value).map_err(D::Error::custom))
+        .transpose()
+}
+
+/// Reads an entity written either as `Entity::to_bits` or its `Debug` form, eg. `"12v1"`
+pub fn entity_from_json(value: &Value) -> Result<Entity, JsonError> {
+    match value {
+        Value::Number(number) => number
+            .as_u64()
+            .and_then(|bits| Entity::try_from_bits(bits).ok())
+            .ok_or_else(|| JsonError::InvalidEntity(value.to_string())),
+        Value::String(string) => {
+            Entity::parse_arg(string).map_err(|_| JsonError::InvalidEntity(value.to_string()))
+        }
+        _ => Err(JsonError::InvalidEntity(value.to_string())),
+    }
+}
+
+/// Deserializes the fields of `T`, treating missing args as an empty struct
+pub fn from_value<T: CommandMeta + DeserializeOwned>(args: Value) -> Result<T, JsonError> {
+    let args = match args {
+        Value::Object(map) if map.is_empty() && T::INFO.params.is_empty() => Value::Null,
+        Value::Null if !T::INFO.params.is_empty() => Value::Object(Default::default()),
+        args => args,
+    };
+    serde_json::from_value(args).map_err(JsonError::Args)
+}
+
+/// Ensures an entity command was given its subject
+pub fn subject<T: CommandMeta>(entity: Option<Entity>) -> Result<Entity, JsonError> {
+    entity.ok_or(JsonError::MissingEntity(T::INFO.name))
+}
+
+/// Finds the command a request refers to, by its id if present and otherwise its name
+pub fn find_command<'a>(
+    registry: &'a CommandRegistry,
+    request: &CommandRequest,
+) -> Result<&'a CommandRegistration, JsonError> {
+    request
+        .id
+        .and_then(|id| registry.get_by_id(id))
+        .or_else(|| registry.get(&request.command))
+        .ok_or_else(|| JsonError::UnknownCommand(request.command.clone()))
+}
+
+/// Upgrades a request's args to the current version of its command
+pub fn migrate(
+    registration: &CommandRegistration,
+    version: Option<u32>,
+    args: Value,
+) -> Result<Value, JsonError> {
+    let info = registration.info();
+    match version {
+        None => Ok(args),
+        Some(version) if version == info.version => Ok(args),
+        Some(version) if version > info.version => {
+            Err(JsonError::UnsupportedVersion(info.name, version))
+        }
+        Some(version) => match registration.migrate() {
+            Some(migrate) => migrate(version, args),
+            None => Err(JsonError::UnsupportedVersion(info.name, version)),
+        },
+    }
+}
+
+/// Deserializes a request into a command, using the commands known to `registry`
+pub fn parse_json_command(
+    registry: &CommandRegistry,
+    request: CommandRequest,
+) -> Result<DynCommand, JsonError> {
+    let registration = find_command(registry, &request)?;
+    let parse = registration
+        .json()
+        .ok_or_else(|| JsonError::Unsupported(request.command.clone()))?;
+    if registration.info().kind == CommandKind::Command && request.entity.is_some() {
+        return Err(JsonError::UnexpectedEntity(registration.info().name));
+    }
+
+    let args = migrate(registration, request.version, request.args)?;
+    parse(request.entity, args)
+}
+
+/// Runs serialized commands, eg. `world.run_json_command(request)`
+pub trait RunJsonCommandExt {
+    fn run_json_command(&mut self, request: CommandRequest) -> Result<(), JsonError>;
+}
+
+impl RunJsonCommandExt for World {
+    fn run_json_command(&mut self, request: CommandRequest) -> Result<(), JsonError> {
+        let command = {
+            let registry = self.get_resource_or_insert_with(CommandRegistry::default);
+            parse_json_command(&registry, request)?
+        };
+        command(self);
+        Ok(())
+    }
+}
+
+/// Errors that may occur while deserializing a command
+#[derive(Debug)]
+pub enum JsonError {
+    /// No command is registered with this name
+    UnknownCommand(String),
+    /// The command exists, but was not generated with `#[command(serde)]`
+    Unsupported(String),
+    /// An entity command was not given an entity
+    MissingEntity(&'static str),
+    /// A non-entity command was given an entity
+    UnexpectedEntity(&'static str),
+    InvalidEntity(String),
+    /// The args did not match the command's fields
+    Args(serde_json::Error),
+    /// The args were serialized by a version of the command we cannot migrate from
+    UnsupportedVersion(&'static str, u32),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::UnknownCommand(name) => write!(f, "unknown command `{name}`"),
+            JsonError::Unsupported(name) => {
+                write!(f, "command `{name}` does not support serialization")
+            }
+            JsonError::MissingEntity(name) => {
+                write!(f, "entity command `{name}` requires an entity")
+            }
+            JsonError::UnexpectedEntity(name) => {
+                write!(f, "command `{name}` does not take an entity")
+            }
+            JsonError::InvalidEntity(value) => write!(f, "invalid entity `{value}`"),
+            JsonError::Args(err) => write!(f, "invalid args: {err}"),
+            JsonError::UnsupportedVersion(name, version) => {
+                write!(
+                    f,
+                    "command `{name}` cannot be migrated from version {version}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
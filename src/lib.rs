@@ -1,53 +1,77 @@
-mod gen;
-mod parse;
+//! A macro for creating bevy `Commands` and `EntityCommands` methods from functions.
+//!
+//! See [`command`] and [`entity_command`] for usage.
 
-use gen::commandify;
+pub mod condition;
+pub mod console;
+pub mod dedup;
+pub mod delay;
+pub mod event;
+pub mod flight;
+#[cfg(feature = "input")]
+pub mod input;
+#[cfg(feature = "serde")]
+pub mod journal;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod pending;
+pub mod priority;
+pub mod registry;
+#[cfg(feature = "remote")]
+pub mod remote;
+#[cfg(feature = "serde")]
+pub mod replicate;
+pub mod schedule;
+#[cfg(feature = "serde")]
+pub mod schema;
+#[cfg(feature = "scripts")]
+pub mod script;
+pub mod sender;
+#[cfg(feature = "ui")]
+pub mod ui;
+pub mod undo;
 
-use proc_macro::TokenStream as ProcTokenStream;
-use syn::punctuated::Punctuated;
-use syn::{parse_macro_input, Error, ItemFn, Meta};
-
-/// Promotes a function to a `Command` struct, and creates an equivalent `Commands` and `World` method via trait extensions
-///
-/// - `#[command(no_trait)]` prevents generating a trait method for `Commands`
-/// - `#[command(no_world)]` prevents generating a trait impl for `World`
-/// - `#[command(name = T)]` will use this name for the method and related struct/trait names
-/// - `#[command(struct_name = T)]` will use this name for the generated struct, defaults to `<Foo>Command`
-/// - `#[command(trait_name = T)]` will use this name for the generated trait, defaults to `Commands<Foo>Ext`
-/// - `#[command(ecs = T)]` to change the crate root to T, defaults to `bevy::ecs`
-/// - `#[command(bevy_ecs)]` to change the crate root to `bevy_ecs`
-///
-/// Note: `T`s may be optionally quoted
-///
-/// Commands may optionally return `&mut Self` to allow chaining their calls
-#[proc_macro_attribute]
-pub fn command(args: ProcTokenStream, input: ProcTokenStream) -> ProcTokenStream {
-    let args = parse_macro_input!(args with Punctuated::<Meta, syn::Token![,]>::parse_terminated);
-    let item = parse_macro_input!(input as ItemFn);
-
-    commandify(args, item, false)
-        .unwrap_or_else(Error::into_compile_error)
-        .into()
-}
-
-/// Promotes a function to an EntityCommand struct, and creates an equivalent EntityCommands method via trait extensions
-///
-/// - `#[entity_command(no_trait)]` prevents generating a trait method for EntityCommands
-/// - `#[entity_command(name = T)]` will use this name for the method and related struct/trait names
-/// - `#[entity_command(struct_name = T)]` will use this name for the generated struct, defaults to `<Foo>EntityCommand`
-/// - `#[entity_command(trait_name = T)]` will use this name for the generated trait, defaults to `EntityCommands<Foo>Ext`
-/// - `#[entity_command(ecs = T)]` to change the crate root to T, defaults to `bevy::ecs`
-/// - `#[entity_command(bevy_ecs)]` to change the crate root to `bevy_ecs`
-///
-/// Note: `T`s may be optionally quoted
-///
-/// Commands may optionally return `&mut Self` to allow chaining their calls
-#[proc_macro_attribute]
-pub fn entity_command(args: ProcTokenStream, input: ProcTokenStream) -> ProcTokenStream {
-    let args = parse_macro_input!(args with Punctuated::<Meta, syn::Token![,]>::parse_terminated);
-    let item = parse_macro_input!(input as ItemFn);
-
-    commandify(args, item, true)
-        .unwrap_or_else(Error::into_compile_error)
-        .into()
-}
+pub use bevy_commandify_macros::{command, entity_command};
+pub use console::{
+    ConsoleArg, ConsoleCommand, ConsoleError, ConsoleInput, ConsolePlugin, RunCommandLineExt,
+    StdinConsolePlugin,
+};
+pub use dedup::{ClearUniqueCommands, CommandDedup, CommandDedupPlugin, OnceCommands};
+pub use delay::{CommandScheduler, CommandSchedulerPlugin, CommandToken, Delay, Repeat, RepeatExt};
+pub use event::AddCommandEventExt;
+pub use flight::{FlightRecord, FlightRecorder, FlightRecorderPlugin};
+#[cfg(all(feature = "input", feature = "serde"))]
+pub use input::InputBinding;
+#[cfg(feature = "input")]
+pub use input::{BindCommandExt, CommandBindings, InputTrigger};
+#[cfg(feature = "serde")]
+pub use journal::{CommandJournal, CommandJournalPlugin, JournalEntry, JournalReplay};
+#[cfg(feature = "serde")]
+pub use json::{CommandRequest, JsonCommand, JsonError, RunJsonCommandExt};
+pub use pending::{OnSpawn, PendingCommandsPlugin, PendingEntityCommands, RegisterOnSpawnExt};
+#[cfg(feature = "reflect")]
+pub use pending::{PendingEntityRequests, PendingRequest};
+pub use priority::{
+    apply_prioritized_commands, ApplyPrioritizedCommands, CommandPriorityPlugin,
+    CommandPriorityQueue,
+};
+pub use registry::{
+    CommandInfo, CommandKind, CommandMeta, CommandRegistration, CommandRegistry, ParamInfo,
+    RegisterCommandExt,
+};
+#[cfg(feature = "remote")]
+pub use remote::{RemotePlugin, RemoteServer};
+#[cfg(feature = "serde")]
+pub use replicate::{
+    ClientId, CommandTransport, LoopbackTransport, ReplicatedCommand, ReplicationClient,
+    ReplicationError, ReplicationPlugin, ReplicationServer,
+};
+pub use schedule::{run_command, AddCommandExt, CommandSystemExt, IntoCommandConfigs};
+#[cfg(feature = "serde")]
+pub use schema::{CommandSchema, JsonSchema};
+#[cfg(feature = "scripts")]
+pub use script::{CommandScript, CommandScriptPlugin, ScriptRunner, ScriptStep};
+pub use sender::{CommandReceiver, CommandSender, CommandSenderPlugin, EntityCommandSender};
+#[cfg(feature = "ui")]
+pub use ui::{CommandButton, EntityCommandButton, RegisterCommandButtonExt};
+pub use undo::{UndoExt, UndoHistory};
@@ -0,0 +1,232 @@
+#[cfg(feature = "reflect")]
+use crate::json::{CommandRequest, JsonError, RunJsonCommandExt};
+#[cfg(feature = "reflect")]
+use crate::registry::CommandMeta;
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::EntityCommand;
+#[cfg(feature = "reflect")]
+use bevy_reflect::std_traits::ReflectDefault;
+#[cfg(feature = "reflect")]
+use bevy_reflect::Reflect;
+use bevy_utils::synccell::SyncCell;
+#[cfg(feature = "reflect")]
+use bevy_utils::tracing::warn;
+#[cfg(feature = "reflect")]
+use serde::Serialize;
+
+type PendingCommand = Box<dyn FnOnce(Entity, &mut World) + Send>;
+
+/// Entity commands stored on an entity, applied to it the first time it is seen and then removed
+///
+/// Allows commands to be declared as part of a bundle, eg. `(Enemy, PendingEntityCommands::new().with(HealEntityCommand { amount: 5 }))`
+///
+/// Cannot be saved in a scene, see `PendingEntityRequests` instead
+#[derive(Component, Default)]
+pub struct PendingEntityCommands {
+    commands: Vec<SyncCell<PendingCommand>>,
+}
+
+impl PendingEntityCommands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a command to be applied, in the order they were added
+    pub fn with(mut self, command: impl EntityCommand) -> Self {
+        self.push(command);
+        self
+    }
+
+    pub fn push(&mut self, command: impl EntityCommand) {
+        self.commands.push(SyncCell::new(Box::new(
+            move |entity: Entity, world: &mut World| command.apply(entity, world),
+        )));
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+/// A serialized entity command stored in [`PendingEntityRequests`]
+#[cfg(feature = "reflect")]
+#[derive(Reflect, Clone, Debug, Default, PartialEq)]
+pub struct PendingRequest {
+    /// The command's name, see `CommandInfo::name`
+    pub command: String,
+    /// The command's id, finding commands that have since been renamed
+    pub id: Option<u64>,
+    /// The version of `args`, migrating older args when applied
+    pub version: Option<u32>,
+    /// The command's fields as JSON
+    pub args: String,
+}
+
+/// Serialized entity commands stored on an entity, applied to it the first time it is seen and then removed
+///
+/// Unlike [`PendingEntityCommands`], may be saved in and loaded from scenes.
+/// Each command must be generated with `#[entity_command(serde)]` and registered in the `CommandRegistry`,
+/// and any entities in their fields are not mapped when a scene is loaded
+#[cfg(feature = "reflect")]
+#[derive(Component, Reflect, Clone, Debug, Default)]
+#[reflect(Component, Default)]
+pub struct PendingEntityRequests {
+    pub requests: Vec<PendingRequest>,
+}
+
+#[cfg(feature = "reflect")]
+impl PendingEntityRequests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a command to be applied, in the order they were added
+    ///
+    /// Panics if the command cannot be serialized, see [`PendingEntityRequests::push`]
+    pub fn with<T: CommandMeta + Serialize>(mut self, command: T) -> Self {
+        if let Err(err) = self.push(&command) {
+            panic!("failed to serialize `{}`: {err}", T::INFO.name);
+        }
+        self
+    }
+
+    pub fn push<T: CommandMeta + Serialize>(&mut self, command: &T) -> Result<(), JsonError> {
+        let request = CommandRequest::new(command, None)?;
+        self.requests.push(PendingRequest {
+            command: request.command,
+            id: request.id,
+            version: request.version,
+            args: request.args.to_string(),
+        });
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+}
+
+/// A single entity command stored on an entity, applied to it the first time it is seen and then removed
+///
+/// Each command type must be registered via [`RegisterOnSpawnExt::register_on_spawn`]
+#[derive(Component)]
+pub struct OnSpawn<C: EntityCommand + Sync>(pub C);
+
+/// Applies [`PendingEntityCommands`] to their entities at the end of each frame
+///
+/// With the `reflect` feature, also registers and applies `PendingEntityRequests`
+pub struct PendingCommandsPlugin;
+
+impl Plugin for PendingCommandsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, apply_pending_entity_commands);
+
+        #[cfg(feature = "reflect")]
+        app.register_type::<PendingEntityRequests>()
+            .register_type::<PendingRequest>()
+            .register_type::<Vec<PendingRequest>>()
+            .register_type::<Option<u64>>()
+            .register_type::<Option<u32>>()
+            .add_systems(
+                PostUpdate,
+                apply_pending_entity_requests.after(apply_pending_entity_commands),
+            );
+    }
+}
+
+/// Registers systems applying [`OnSpawn`] commands, eg. `app.register_on_spawn::<HealEntityCommand>()`
+pub trait RegisterOnSpawnExt {
+    fn register_on_spawn<C: EntityCommand + Sync>(&mut self) -> &mut Self;
+}
+
+impl RegisterOnSpawnExt for App {
+    fn register_on_spawn<C: EntityCommand + Sync>(&mut self) -> &mut Self {
+        self.add_systems(PostUpdate, apply_on_spawn::<C>)
+    }
+}
+
+fn apply_pending_entity_commands(world: &mut World) {
+    let entities = world
+        .query_filtered::<Entity, With<PendingEntityCommands>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+
+    for entity in entities {
+        // an earlier command may have despawned this entity
+        let Some(mut entity_mut) = world.get_entity_mut(entity) else {
+            continue;
+        };
+        let Some(pending) = entity_mut.take::<PendingEntityCommands>() else {
+            continue;
+        };
+        for command in pending.commands {
+            SyncCell::to_inner(command)(entity, world);
+        }
+    }
+}
+
+#[cfg(feature = "reflect")]
+fn apply_pending_entity_requests(world: &mut World) {
+    let entities = world
+        .query_filtered::<Entity, With<PendingEntityRequests>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+
+    for entity in entities {
+        let Some(mut entity_mut) = world.get_entity_mut(entity) else {
+            continue;
+        };
+        let Some(pending) = entity_mut.take::<PendingEntityRequests>() else {
+            continue;
+        };
+        for request in pending.requests {
+            let args = match serde_json::from_str(&request.args) {
+                Ok(args) => args,
+                Err(err) => {
+                    warn!(
+                        "invalid args for pending command `{}`: {err}",
+                        request.command
+                    );
+                    continue;
+                }
+            };
+            let name = request.command.clone();
+            let request = CommandRequest {
+                command: request.command,
+                entity: Some(entity),
+                args,
+                id: request.id,
+                version: request.version,
+            };
+            if let Err(err) = world.run_json_command(request) {
+                warn!("failed to apply pending command `{name}`: {err}");
+            }
+        }
+    }
+}
+
+fn apply_on_spawn<C: EntityCommand + Sync>(world: &mut World) {
+    let entities = world
+        .query_filtered::<Entity, With<OnSpawn<C>>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+
+    for entity in entities {
+        let Some(mut entity_mut) = world.get_entity_mut(entity) else {
+            continue;
+        };
+        let Some(OnSpawn(command)) = entity_mut.take::<OnSpawn<C>>() else {
+            continue;
+        };
+        command.apply(entity, world);
+    }
+}
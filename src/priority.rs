@@ -0,0 +1,206 @@
+use crate::console::DynCommand;
+use bevy_app::{
+    App, First, Last, MainScheduleOrder, Plugin, PostStartup, PostUpdate, PreStartup, PreUpdate,
+    Startup, Update,
+};
+use bevy_ecs::prelude::*;
+use bevy_ecs::schedule::ScheduleLabel;
+use bevy_utils::synccell::SyncCell;
+use bevy_utils::HashMap;
+use std::cell::Cell;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::mem;
+
+thread_local! {
+    /// Whether buffered commands are currently being applied on this thread, so that they are not buffered again
+    static FLUSHING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs after each of the main schedules, applying the commands buffered during it
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ApplyPrioritizedCommands;
+
+struct PrioritizedEntry {
+    name: &'static str,
+    priority: i32,
+    /// The names of the commands that must be applied before this one
+    after: &'static [&'static str],
+    /// Entity commands are dropped if their entity no longer exists
+    entity: Option<Entity>,
+    command: SyncCell<DynCommand>,
+}
+
+/// Buffers `#[command(priority = N)]` and `#[command(after = T)]` commands, so that they are applied in a deterministic order
+///
+/// Commands are applied in order of highest priority, then the order they were issued, except that commands are always applied
+/// after the buffered commands named by their `after`
+#[derive(Resource, Default)]
+pub struct CommandPriorityQueue {
+    entries: Vec<PrioritizedEntry>,
+}
+
+impl CommandPriorityQueue {
+    /// The number of buffered commands
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drops every buffered command without applying them
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Removes every buffered command, in the order they should be applied
+    fn drain_ordered(&mut self) -> Vec<PrioritizedEntry> {
+        let entries = mem::take(&mut self.entries);
+        let mut by_name = HashMap::<&str, Vec<usize>>::new();
+        for (index, entry) in entries.iter().enumerate() {
+            by_name.entry(entry.name).or_default().push(index);
+        }
+
+        // how many commands each command is still waiting on, and which commands are waiting on each command
+        let mut waiting = vec![0; entries.len()];
+        let mut dependents = vec![Vec::new(); entries.len()];
+        for (index, entry) in entries.iter().enumerate() {
+            for &before in entry
+                .after
+                .iter()
+                .filter_map(|name| by_name.get(name))
+                .flatten()
+            {
+                if before != index {
+                    dependents[before].push(index);
+                    waiting[index] += 1;
+                }
+            }
+        }
+
+        // entries are kept in issue order, so the first of the highest priority wins ties
+        let order_key = |index: usize| (entries[index].priority, Reverse(index));
+        let mut ready: BinaryHeap<_> = (0..entries.len())
+            .filter(|&index| waiting[index] == 0)
+            .map(order_key)
+            .collect();
+        let mut ordered = vec![false; entries.len()];
+        let mut order = Vec::with_capacity(entries.len());
+        while order.len() < entries.len() {
+            let index = match ready.pop() {
+                Some((_, Reverse(index))) => index,
+                // cyclic `after` constraints fall back to priority alone
+                None => (0..entries.len())
+                    .filter(|&index| !ordered[index])
+                    .max_by_key(|&index| order_key(index))
+                    .unwrap(),
+            };
+            ordered[index] = true;
+            order.push(index);
+            for &dependent in &dependents[index] {
+                waiting[dependent] -= 1;
+                if waiting[dependent] == 0 && !ordered[dependent] {
+                    ready.push(order_key(dependent));
+                }
+            }
+        }
+
+        let mut entries: Vec<_> = entries.into_iter().map(Some).collect();
+        order
+            .into_iter()
+            .map(|index| entries[index].take().unwrap())
+            .collect()
+    }
+}
+
+/// Called by generated `apply` impls, returns true if a prioritized command should be buffered via [`push`] rather than applied
+///
+/// Always false without a [`CommandPriorityQueue`], or while buffered or stored `merge` commands are being applied,
+/// so that commands with both `priority` and `merge` are applied once merged
+pub fn defer(world: &World) -> bool {
+    !FLUSHING.with(Cell::get)
+        && !crate::dedup::flushing()
+        && world.contains_resource::<CommandPriorityQueue>()
+}
+
+/// Buffers a prioritized command, until [`apply_prioritized_commands`] runs
+pub fn push(
+    world: &mut World,
+    name: &'static str,
+    priority: i32,
+    after: &'static [&'static str],
+    entity: Option<Entity>,
+    command: DynCommand,
+) {
+    world
+        .resource_mut::<CommandPriorityQueue>()
+        .entries
+        .push(PrioritizedEntry {
+            name,
+            priority,
+            after,
+            entity,
+            command: SyncCell::new(command),
+        });
+}
+
+/// Applies the buffered prioritized commands, in order
+///
+/// Runs after each of the main schedules via the [`CommandPriorityPlugin`], but may also be added after an `apply_deferred`
+pub fn apply_prioritized_commands(world: &mut World) {
+    let Some(mut queue) = world.get_resource_mut::<CommandPriorityQueue>() else {
+        return;
+    };
+    if queue.is_empty() {
+        return;
+    }
+    let entries = queue.drain_ordered();
+    let _flushing = FlushGuard::new();
+
+    for entry in entries {
+        if entry
+            .entity
+            .is_some_and(|entity| world.get_entity(entity).is_none())
+        {
+            continue;
+        }
+        SyncCell::to_inner(entry.command)(world);
+    }
+}
+
+/// Marks buffered commands as being applied on this thread, until dropped
+struct FlushGuard(bool);
+
+impl FlushGuard {
+    fn new() -> Self {
+        Self(FLUSHING.with(|flushing| flushing.replace(true)))
+    }
+}
+
+impl Drop for FlushGuard {
+    fn drop(&mut self) {
+        FLUSHING.with(|flushing| flushing.set(self.0));
+    }
+}
+
+/// Buffers prioritized commands, and applies them after each of the main schedules
+pub struct CommandPriorityPlugin;
+
+impl Plugin for CommandPriorityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CommandPriorityQueue>()
+            .add_systems(ApplyPrioritizedCommands, apply_prioritized_commands);
+
+        let mut order = app.world.resource_mut::<MainScheduleOrder>();
+        order.insert_after(First, ApplyPrioritizedCommands);
+        order.insert_after(PreUpdate, ApplyPrioritizedCommands);
+        order.insert_after(Update, ApplyPrioritizedCommands);
+        order.insert_after(PostUpdate, ApplyPrioritizedCommands);
+        order.insert_after(Last, ApplyPrioritizedCommands);
+        order.insert_startup_after(PreStartup, ApplyPrioritizedCommands);
+        order.insert_startup_after(Startup, ApplyPrioritizedCommands);
+        order.insert_startup_after(PostStartup, ApplyPrioritizedCommands);
+    }
+}
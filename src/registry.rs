@@ -0,0 +1,265 @@
+use crate::console::ConsoleParseFn;
+#[cfg(feature = "serde")]
+use crate::json::{JsonParseFn, MigrateFn};
+#[cfg(feature = "serde")]
+use crate::replicate::ReplicateFn;
+#[cfg(feature = "serde")]
+use crate::schema::SchemaFn;
+use bevy_app::App;
+use bevy_ecs::prelude::*;
+use bevy_utils::HashMap;
+use std::any::{type_name, TypeId};
+
+/// Whether a registered command is a `Command` or an `EntityCommand`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CommandKind {
+    /// Generated by `#[command]`
+    Command,
+    /// Generated by `#[entity_command]`, and applies to a subject entity
+    EntityCommand,
+}
+
+/// A single field of a generated command struct
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParamInfo {
+    /// The name of the fn parameter and struct field
+    pub name: &'static str,
+    /// The type as it was written in the fn signature
+    pub ty: &'static str,
+}
+
+/// Static metadata describing a commandified fn
+#[derive(Clone, Copy, Debug)]
+pub struct CommandInfo {
+    /// The name of the generated method, eg. `foo` for `commands.foo()`
+    pub name: &'static str,
+    /// The name of the generated struct, eg. `FooCommand`
+    pub struct_name: &'static str,
+    pub kind: CommandKind,
+    /// The fields of the generated struct, which excludes the `World` and subject `Entity`
+    pub params: &'static [ParamInfo],
+    /// The fn's doc comments, one line per `///`
+    pub docs: &'static str,
+    /// A stable identifier for serialized commands, from `#[command(id = N)]` or a hash of the name
+    pub id: u64,
+    /// The version of our serialized fields, from `#[command(version = N)]`, starting at 1
+    pub version: u32,
+}
+
+/// Implemented for every struct generated by `#[command]` and `#[entity_command]`
+pub trait CommandMeta: Sized + Send + 'static {
+    const INFO: CommandInfo;
+
+    /// Builds the entry stored in [`CommandRegistry`] for this command
+    fn registration() -> CommandRegistration {
+        CommandRegistration::of::<Self>()
+    }
+}
+
+/// A command's entry in the [`CommandRegistry`]
+#[derive(Clone, Debug)]
+pub struct CommandRegistration {
+    info: CommandInfo,
+    type_id: TypeId,
+    type_name: &'static str,
+    console: Option<ConsoleParseFn>,
+    #[cfg(feature = "serde")]
+    json: Option<JsonParseFn>,
+    #[cfg(feature = "serde")]
+    migrate: Option<MigrateFn>,
+    #[cfg(feature = "serde")]
+    schema: Option<SchemaFn>,
+    #[cfg(feature = "serde")]
+    replicate: Option<ReplicateFn>,
+}
+
+impl CommandRegistration {
+    pub fn of<T: CommandMeta>() -> Self {
+        Self {
+            info: T::INFO,
+            type_id: TypeId::of::<T>(),
+            type_name: type_name::<T>(),
+            console: None,
+            #[cfg(feature = "serde")]
+            json: None,
+            #[cfg(feature = "serde")]
+            migrate: None,
+            #[cfg(feature = "serde")]
+            schema: None,
+            #[cfg(feature = "serde")]
+            replicate: None,
+        }
+    }
+
+    /// Allows this command to be parsed from a command line
+    pub fn with_console(mut self, parse: ConsoleParseFn) -> Self {
+        self.console = Some(parse);
+        self
+    }
+
+    /// Allows this command to be deserialized from JSON
+    #[cfg(feature = "serde")]
+    pub fn with_json(mut self, parse: JsonParseFn) -> Self {
+        self.json = Some(parse);
+        self
+    }
+
+    /// Upgrades args serialized by older versions of this command
+    #[cfg(feature = "serde")]
+    pub fn with_migrate(mut self, migrate: MigrateFn) -> Self {
+        self.migrate = Some(migrate);
+        self
+    }
+
+    /// Describes this command's serialized fields
+    #[cfg(feature = "serde")]
+    pub fn with_schema(mut self, schema: SchemaFn) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Allows this command to be received from a replication client
+    #[cfg(feature = "serde")]
+    pub fn with_replicate(mut self, replicate: ReplicateFn) -> Self {
+        self.replicate = Some(replicate);
+        self
+    }
+
+    pub fn info(&self) -> &CommandInfo {
+        &self.info
+    }
+
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// The fully qualified name of the generated struct
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    pub fn console(&self) -> Option<ConsoleParseFn> {
+        self.console
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn json(&self) -> Option<JsonParseFn> {
+        self.json
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn migrate(&self) -> Option<MigrateFn> {
+        self.migrate
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn schema(&self) -> Option<SchemaFn> {
+        self.schema
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn replicate(&self) -> Option<ReplicateFn> {
+        self.replicate
+    }
+}
+
+/// Holds metadata for every registered command, so they may be enumerated at runtime
+///
+/// Commands are registered via [`RegisterCommandExt::register_command`]
+#[derive(Resource, Default, Debug)]
+pub struct CommandRegistry {
+    commands: Vec<CommandRegistration>,
+    by_name: HashMap<&'static str, usize>,
+    by_type: HashMap<TypeId, usize>,
+    by_id: HashMap<u64, usize>,
+}
+
+impl CommandRegistry {
+    /// Adds `T` to the registry, replacing any command previously registered under the same name
+    pub fn register<T: CommandMeta>(&mut self) -> &mut Self {
+        self.add(T::registration())
+    }
+
+    /// Adds a pre-built registration, replacing any command previously registered under the same name
+    ///
+    /// Panics if a different command was registered with the same id
+    pub fn add(&mut self, registration: CommandRegistration) -> &mut Self {
+        let name = registration.info.name;
+        let type_id = registration.type_id;
+        let id = registration.info.id;
+        if let Some(&index) = self.by_id.get(&id) {
+            let other = self.commands[index].info.name;
+            if other != name {
+                panic!("commands `{other}` and `{name}` have the same id {id}, set a unique `#[command(id = N)]` for one of them");
+            }
+        }
+        if let Some(&index) = self.by_name.get(name) {
+            self.by_type.remove(&self.commands[index].type_id);
+            self.by_id.remove(&self.commands[index].info.id);
+            self.commands[index] = registration;
+            self.by_type.insert(type_id, index);
+            self.by_id.insert(id, index);
+        } else {
+            let index = self.commands.len();
+            self.commands.push(registration);
+            self.by_name.insert(name, index);
+            self.by_type.insert(type_id, index);
+            self.by_id.insert(id, index);
+        }
+        self
+    }
+
+    /// Finds a command by its method name
+    pub fn get(&self, name: &str) -> Option<&CommandRegistration> {
+        self.by_name.get(name).map(|&index| &self.commands[index])
+    }
+
+    /// Finds a command by its generated struct
+    pub fn get_by_type<T: 'static>(&self) -> Option<&CommandRegistration> {
+        self.by_type
+            .get(&TypeId::of::<T>())
+            .map(|&index| &self.commands[index])
+    }
+
+    /// Finds a command by its stable id, see [`CommandInfo::id`]
+    pub fn get_by_id(&self, id: u64) -> Option<&CommandRegistration> {
+        self.by_id.get(&id).map(|&index| &self.commands[index])
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.by_name.contains_key(name)
+    }
+
+    /// Iterates all commands in the order they were registered
+    pub fn iter(&self) -> impl Iterator<Item = &CommandRegistration> {
+        self.commands.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+/// Registers commands into the [`CommandRegistry`] resource, inserting it if necessary
+pub trait RegisterCommandExt {
+    fn register_command<T: CommandMeta>(&mut self) -> &mut Self;
+}
+
+impl RegisterCommandExt for World {
+    fn register_command<T: CommandMeta>(&mut self) -> &mut Self {
+        self.get_resource_or_insert_with(CommandRegistry::default)
+            .register::<T>();
+        self
+    }
+}
+
+impl RegisterCommandExt for App {
+    fn register_command<T: CommandMeta>(&mut self) -> &mut Self {
+        self.world.register_command::<T>();
+        self
+    }
+}
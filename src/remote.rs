@@ -0,0 +1,257 @@
+use crate::json::{CommandRequest, RunJsonCommandExt};
+use crate::registry::CommandRegistry;
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use bevy_utils::HashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{
+    IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+/// How many connections a [`RemoteServer`] serves at once by default
+pub const DEFAULT_MAX_CONNECTIONS: usize = 8;
+
+/// A single request received by the [`RemoteServer`], one per line
+///
+/// eg. `{ "id": 1, "command": "heal", "entity": "12v1", "args": { "amount": 5 } }`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RemoteRequest {
+    /// Echoed back in the response so that clients may match them up
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub id: Value,
+    #[serde(flatten)]
+    pub request: CommandRequest,
+}
+
+/// The reply to a [`RemoteRequest`], sent once the command has been applied
+///
+/// eg. `{ "id": 1, "ok": true }` or `{ "id": 1, "ok": false, "error": "unknown command `foo`" }`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RemoteResponse {
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub id: Value,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A line received from a client, and where to send its response
+struct Incoming {
+    line: String,
+    reply: Sender<String>,
+}
+
+/// The connections being served, so that they may be closed when the server is dropped
+#[derive(Default)]
+struct Connections {
+    next_id: u64,
+    open: HashMap<u64, TcpStream>,
+}
+
+/// Accepts newline-delimited JSON requests over TCP and queues them to be run against the world
+///
+/// Each line is deserialized into a [`RemoteRequest`], and a [`RemoteResponse`] is written back on the same connection.
+/// Each connection is served by its own thread, up to a maximum, and further connections are closed until one ends.
+///
+/// Dropping the server, eg. with its `App`, stops listening and closes every connection
+#[derive(Resource)]
+pub struct RemoteServer {
+    local_addr: SocketAddr,
+    receiver: Mutex<Receiver<Incoming>>,
+    shutdown: Arc<AtomicBool>,
+    connections: Arc<Mutex<Connections>>,
+    accept: Option<JoinHandle<()>>,
+}
+
+impl RemoteServer {
+    /// Starts listening on a background thread, serving up to [`DEFAULT_MAX_CONNECTIONS`] at once
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Self::bind_with_max_connections(addr, DEFAULT_MAX_CONNECTIONS)
+    }
+
+    /// Starts listening on a background thread, serving up to `max_connections` at once
+    pub fn bind_with_max_connections(
+        addr: impl ToSocketAddrs,
+        max_connections: usize,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        let (sender, receiver) = channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let connections = Arc::new(Mutex::new(Connections::default()));
+
+        let accept = {
+            let shutdown = shutdown.clone();
+            let connections = connections.clone();
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::Acquire) {
+                        break;
+                    }
+                    let Ok(stream) = stream else { continue };
+                    let id = {
+                        let mut connections = connections.lock().unwrap();
+                        if connections.open.len() >= max_connections {
+                            continue;
+                        }
+                        let Ok(clone) = stream.try_clone() else {
+                            continue;
+                        };
+                        let id = connections.next_id;
+                        connections.next_id += 1;
+                        connections.open.insert(id, clone);
+                        id
+                    };
+                    let sender = sender.clone();
+                    let connections = connections.clone();
+                    std::thread::spawn(move || {
+                        serve(stream, sender);
+                        connections.lock().unwrap().open.remove(&id);
+                    });
+                }
+            })
+        };
+
+        Ok(Self {
+            local_addr,
+            receiver: Mutex::new(receiver),
+            shutdown,
+            connections,
+            accept: Some(accept),
+        })
+    }
+
+    /// The address we are listening on, useful when bound to port `0`
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Drop for RemoteServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Release);
+        // wakes the accept thread, which sees the shutdown before serving us
+        let mut wake = self.local_addr;
+        if wake.ip().is_unspecified() {
+            wake.set_ip(match wake.ip() {
+                IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+                IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::LOCALHOST),
+            });
+        }
+        if TcpStream::connect(wake).is_ok() {
+            if let Some(accept) = self.accept.take() {
+                let _ = accept.join();
+            }
+        }
+        // no more connections are opened once the accept thread has stopped
+        for (_, stream) in self.connections.lock().unwrap().open.drain() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+fn serve(stream: TcpStream, sender: Sender<Incoming>) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    let (reply, responses) = channel();
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if sender
+            .send(Incoming {
+                line,
+                reply: reply.clone(),
+            })
+            .is_err()
+        {
+            break;
+        }
+        // the server has shut down if our reply never arrives
+        let Ok(response) = responses.recv() else {
+            break;
+        };
+        if writeln!(writer, "{response}").is_err() {
+            break;
+        }
+    }
+}
+
+/// Listens for [`RemoteRequest`]s on a local socket, and runs them each frame
+///
+/// Commands must be generated with `#[command(serde)]` and registered to be found
+pub struct RemotePlugin {
+    addr: String,
+    max_connections: usize,
+}
+
+impl RemotePlugin {
+    /// eg. `RemotePlugin::new("127.0.0.1:7878")`
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self {
+            addr: addr.into(),
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+        }
+    }
+
+    /// Serves up to `max_connections` at once, rather than [`DEFAULT_MAX_CONNECTIONS`]
+    pub fn with_max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+}
+
+impl Plugin for RemotePlugin {
+    fn build(&self, app: &mut App) {
+        let server =
+            RemoteServer::bind_with_max_connections(self.addr.as_str(), self.max_connections)
+                .unwrap_or_else(|err| {
+                    panic!("failed to bind remote server to {}: {err}", self.addr)
+                });
+
+        app.init_resource::<CommandRegistry>()
+            .insert_resource(server)
+            .add_systems(Update, run_remote_requests);
+    }
+}
+
+fn run_remote_requests(world: &mut World) {
+    let incoming = {
+        let server = world.resource::<RemoteServer>();
+        let receiver = server.receiver.lock().unwrap();
+        receiver.try_iter().collect::<Vec<_>>()
+    };
+
+    for Incoming { line, reply } in incoming {
+        let response = match serde_json::from_str::<RemoteRequest>(&line) {
+            Ok(RemoteRequest { id, request }) => match world.run_json_command(request) {
+                Ok(()) => RemoteResponse {
+                    id,
+                    ok: true,
+                    error: None,
+                },
+                Err(err) => RemoteResponse {
+                    id,
+                    ok: false,
+                    error: Some(err.to_string()),
+                },
+            },
+            Err(err) => RemoteResponse {
+                id: Value::Null,
+                ok: false,
+                error: Some(format!("invalid request: {err}")),
+            },
+        };
+        // the client may have disconnected while we were busy
+        let _ = reply.send(serde_json::to_string(&response).unwrap());
+    }
+}
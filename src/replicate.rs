@@ -0,0 +1,314 @@
+use crate::console::DynCommand;
+use crate::json::{self, CommandRequest, JsonError};
+use crate::registry::{CommandKind, CommandMeta, CommandRegistry};
+use bevy_app::{App, First, Last, Plugin};
+use bevy_ecs::entity::EntityMapper;
+use bevy_ecs::prelude::*;
+use bevy_utils::tracing::{error, warn};
+use bevy_utils::HashMap;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+/// Deserializes a replicated command and maps its entities, stored in the `CommandRegistry`
+pub type ReplicateFn =
+    fn(Option<Entity>, Value, &mut ReplicationMapper) -> Result<DynCommand, JsonError>;
+
+/// Implemented for commands generated with `#[command(replicate)]`
+pub trait ReplicatedCommand {
+    /// Builds our command from its serialized fields, mapping any client entities to server entities
+    fn from_replicated(
+        entity: Option<Entity>,
+        args: Value,
+        mapper: &mut ReplicationMapper,
+    ) -> Result<DynCommand, JsonError>;
+}
+
+/// Delivers serialized commands between a client and the server
+///
+/// Each message is a single JSON encoded `CommandRequest`
+pub trait CommandTransport: Send + Sync + 'static {
+    fn send(&mut self, message: Vec<u8>) -> io::Result<()>;
+
+    /// Every message received since the last call, in the order they were sent
+    fn receive(&mut self) -> Vec<Vec<u8>>;
+}
+
+/// An in-process transport, connecting two worlds without a network
+pub struct LoopbackTransport {
+    outgoing: Arc<Mutex<VecDeque<Vec<u8>>>>,
+    incoming: Arc<Mutex<VecDeque<Vec<u8>>>>,
+}
+
+impl LoopbackTransport {
+    /// Two transports connected to each other, eg. one for the client and one for the server
+    pub fn pair() -> (Self, Self) {
+        let a = Arc::new(Mutex::new(VecDeque::new()));
+        let b = Arc::new(Mutex::new(VecDeque::new()));
+        (
+            Self {
+                outgoing: a.clone(),
+                incoming: b.clone(),
+            },
+            Self {
+                outgoing: b,
+                incoming: a,
+            },
+        )
+    }
+}
+
+impl CommandTransport for LoopbackTransport {
+    fn send(&mut self, message: Vec<u8>) -> io::Result<()> {
+        self.outgoing.lock().unwrap().push_back(message);
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Vec<Vec<u8>> {
+        self.incoming.lock().unwrap().drain(..).collect()
+    }
+}
+
+/// Sends replicated commands to the server instead of applying them
+///
+/// Commands are queued as they are applied, and sent at the end of each frame by the [`ReplicationPlugin`]
+#[derive(Resource)]
+pub struct ReplicationClient {
+    transport: Box<dyn CommandTransport>,
+    outbox: Vec<CommandRequest>,
+}
+
+impl ReplicationClient {
+    pub fn new(transport: impl CommandTransport) -> Self {
+        Self {
+            transport: Box::new(transport),
+            outbox: Vec::new(),
+        }
+    }
+
+    /// Commands waiting to be sent
+    pub fn outbox(&self) -> &[CommandRequest] {
+        &self.outbox
+    }
+
+    /// Sends every queued command
+    pub fn flush(&mut self) -> io::Result<()> {
+        for request in self.outbox.drain(..) {
+            let message = serde_json::to_vec(&request)?;
+            self.transport.send(message)?;
+        }
+        Ok(())
+    }
+}
+
+/// Identifies a client connected to the [`ReplicationServer`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ClientId(pub usize);
+
+struct ReplicatedClient {
+    transport: Box<dyn CommandTransport>,
+    /// client entity -> server entity
+    entities: HashMap<Entity, Entity>,
+}
+
+/// Receives replicated commands from clients, and applies them to our world
+///
+/// Only commands generated with `#[command(replicate)]` and registered in the `CommandRegistry` are accepted
+#[derive(Resource, Default)]
+pub struct ReplicationServer {
+    clients: Vec<ReplicatedClient>,
+}
+
+impl ReplicationServer {
+    pub fn add_client(&mut self, transport: impl CommandTransport) -> ClientId {
+        self.clients.push(ReplicatedClient {
+            transport: Box::new(transport),
+            entities: HashMap::default(),
+        });
+        ClientId(self.clients.len() - 1)
+    }
+
+    /// Maps an entity sent by `client` to one of our own
+    ///
+    /// Unmapped entities are given a new empty entity when first received
+    pub fn map_entity(
+        &mut self,
+        client: ClientId,
+        client_entity: Entity,
+        server_entity: Entity,
+    ) -> Result<(), ReplicationError> {
+        self.clients
+            .get_mut(client.0)
+            .ok_or(ReplicationError::UnknownClient(client))?
+            .entities
+            .insert(client_entity, server_entity);
+        Ok(())
+    }
+
+    /// The server entity that `client_entity` has been mapped to
+    pub fn server_entity(&self, client: ClientId, client_entity: Entity) -> Option<Entity> {
+        self.clients
+            .get(client.0)?
+            .entities
+            .get(&client_entity)
+            .copied()
+    }
+}
+
+/// Maps entities received from a client to server entities, spawning any that are unknown
+pub struct ReplicationMapper<'a> {
+    entities: &'a mut HashMap<Entity, Entity>,
+    world: &'a mut World,
+}
+
+impl EntityMapper for ReplicationMapper<'_> {
+    fn map_entity(&mut self, entity: Entity) -> Entity {
+        *self
+            .entities
+            .entry(entity)
+            .or_insert_with(|| self.world.spawn_empty().id())
+    }
+}
+
+/// Called by generated `apply` impls, returns true if our command was sent rather than applied
+pub fn send<T: CommandMeta + Serialize>(
+    world: &mut World,
+    command: &T,
+    entity: Option<Entity>,
+) -> bool {
+    let Some(mut client) = world.get_resource_mut::<ReplicationClient>() else {
+        return false;
+    };
+    match CommandRequest::new(command, entity) {
+        Ok(request) => client.outbox.push(request),
+        Err(err) => error!("failed to replicate `{}`: {err}", T::INFO.name),
+    }
+    true
+}
+
+/// Deserializes and applies a single message received from `client`
+pub fn apply_replicated(
+    world: &mut World,
+    client: ClientId,
+    message: &[u8],
+) -> Result<(), ReplicationError> {
+    let request =
+        serde_json::from_slice::<CommandRequest>(message).map_err(ReplicationError::Message)?;
+
+    if !world.contains_resource::<ReplicationServer>() {
+        return Err(ReplicationError::NoServer);
+    }
+    world.init_resource::<CommandRegistry>();
+
+    let command = world.resource_scope(|world, registry: Mut<CommandRegistry>| {
+        let registration = json::find_command(&registry, &request)?;
+        let Some(replicate) = registration.replicate() else {
+            return Err(ReplicationError::NotReplicated(request.command.clone()));
+        };
+        if registration.info().kind == CommandKind::Command && request.entity.is_some() {
+            return Err(JsonError::UnexpectedEntity(registration.info().name).into());
+        }
+
+        world.resource_scope(|world, mut server: Mut<ReplicationServer>| {
+            let client = server
+                .clients
+                .get_mut(client.0)
+                .ok_or(ReplicationError::UnknownClient(client))?;
+            let mut mapper = ReplicationMapper {
+                entities: &mut client.entities,
+                world,
+            };
+            let args = json::migrate(registration, request.version, request.args)?;
+            Ok(replicate(request.entity, args, &mut mapper)?)
+        })
+    })?;
+    command(world);
+    Ok(())
+}
+
+/// Errors that may occur while applying a replicated command
+#[derive(Debug)]
+pub enum ReplicationError {
+    /// The message was not a valid `CommandRequest`
+    Message(serde_json::Error),
+    /// The command exists, but was not generated with `#[command(replicate)]`
+    NotReplicated(String),
+    /// There is no `ReplicationServer` resource to receive the command
+    NoServer,
+    UnknownClient(ClientId),
+    Json(JsonError),
+}
+
+impl From<JsonError> for ReplicationError {
+    fn from(err: JsonError) -> Self {
+        ReplicationError::Json(err)
+    }
+}
+
+impl fmt::Display for ReplicationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplicationError::Message(err) => write!(f, "invalid message: {err}"),
+            ReplicationError::NotReplicated(name) => {
+                write!(f, "command `{name}` is not replicated")
+            }
+            ReplicationError::NoServer => write!(f, "no replication server"),
+            ReplicationError::UnknownClient(client) => write!(f, "unknown client {}", client.0),
+            ReplicationError::Json(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ReplicationError {}
+
+/// Sends commands from a [`ReplicationClient`], and applies commands received by a [`ReplicationServer`]
+///
+/// Insert either resource to act as a client or a server
+pub struct ReplicationPlugin;
+
+impl Plugin for ReplicationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CommandRegistry>()
+            .add_systems(
+                First,
+                receive_replicated_commands.run_if(resource_exists::<ReplicationServer>),
+            )
+            .add_systems(
+                Last,
+                send_replicated_commands.run_if(resource_exists::<ReplicationClient>),
+            );
+    }
+}
+
+fn send_replicated_commands(mut client: ResMut<ReplicationClient>) {
+    if let Err(err) = client.flush() {
+        error!("failed to send replicated commands: {err}");
+    }
+}
+
+fn receive_replicated_commands(world: &mut World) {
+    let messages = {
+        let mut server = world.resource_mut::<ReplicationServer>();
+        server
+            .clients
+            .iter_mut()
+            .enumerate()
+            .flat_map(|(id, client)| {
+                client
+                    .transport
+                    .receive()
+                    .into_iter()
+                    .map(move |message| (ClientId(id), message))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    for (client, message) in messages {
+        if let Err(err) = apply_replicated(world, client, &message) {
+            warn!("failed to apply command from client {}: {err}", client.0);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use crate::registry::CommandMeta;
+use bevy_app::App;
+use bevy_ecs::prelude::*;
+use bevy_ecs::schedule::{Condition, IntoSystemSet, ScheduleLabel, SystemConfigs};
+use bevy_ecs::system::Command;
+
+/// A system queueing a clone of `command` each time it runs
+///
+/// Allows commands to be scheduled like any other system, eg. `app.add_systems(Update, run_command(FooCommand { n: 1 }).run_if(in_state(GameState::Playing)))`
+pub fn run_command<C: Command + Clone + Sync>(command: C) -> impl FnMut(Commands) {
+    move |mut commands: Commands| commands.add(command.clone())
+}
+
+/// Converts commands, or commands configured via [`CommandSystemExt`], into the systems added by [`AddCommandExt`]
+pub trait IntoCommandConfigs<Marker> {
+    fn into_command_configs(self) -> SystemConfigs;
+}
+
+impl<C: Command + Clone + Sync> IntoCommandConfigs<()> for C {
+    fn into_command_configs(self) -> SystemConfigs {
+        run_command(self).into_configs()
+    }
+}
+
+impl IntoCommandConfigs<SystemConfigs> for SystemConfigs {
+    fn into_command_configs(self) -> SystemConfigs {
+        self
+    }
+}
+
+/// Configures a command's [`run_command`] system, so that it may be scheduled via [`AddCommandExt`]
+///
+/// eg. `app.add_command(Update, SpawnEnemyCommand { level: 1 }.run_if(on_timer(Duration::from_secs(5))))`
+pub trait CommandSystemExt: CommandMeta + Command + Clone + Sync {
+    /// Only queues the command when `condition` is true
+    fn run_if<M>(self, condition: impl Condition<M>) -> SystemConfigs {
+        run_command(self).run_if(condition)
+    }
+
+    /// Queues the command before `set` runs
+    fn before<M>(self, set: impl IntoSystemSet<M>) -> SystemConfigs {
+        run_command(self).before(set)
+    }
+
+    /// Queues the command after `set` runs
+    fn after<M>(self, set: impl IntoSystemSet<M>) -> SystemConfigs {
+        run_command(self).after(set)
+    }
+
+    /// Queues the command as part of `set`
+    fn in_set(self, set: impl SystemSet) -> SystemConfigs {
+        run_command(self).in_set(set)
+    }
+}
+
+impl<C: CommandMeta + Command + Clone + Sync> CommandSystemExt for C {}
+
+/// Schedules commands without writing wrapper systems, for commands generated with `#[command(clone)]`
+pub trait AddCommandExt {
+    /// Queues a clone of `command` each time `schedule` runs, eg. `app.add_command(Startup, SpawnPlayerCommand)`
+    ///
+    /// `command` may also be configured via [`CommandSystemExt`], eg. `app.add_command(Update, SaveCommand.run_if(on_timer(interval)))`
+    fn add_command<M>(
+        &mut self,
+        schedule: impl ScheduleLabel,
+        command: impl IntoCommandConfigs<M>,
+    ) -> &mut Self;
+
+    /// Queues a clone of `command` each time `state` is entered
+    fn add_command_on_enter<S: States, M>(
+        &mut self,
+        state: S,
+        command: impl IntoCommandConfigs<M>,
+    ) -> &mut Self {
+        self.add_command(OnEnter(state), command)
+    }
+
+    /// Queues a clone of `command` each time `state` is exited
+    fn add_command_on_exit<S: States, M>(
+        &mut self,
+        state: S,
+        command: impl IntoCommandConfigs<M>,
+    ) -> &mut Self {
+        self.add_command(OnExit(state), command)
+    }
+}
+
+impl AddCommandExt for App {
+    fn add_command<M>(
+        &mut self,
+        schedule: impl ScheduleLabel,
+        command: impl IntoCommandConfigs<M>,
+    ) -> &mut Self {
+        self.add_systems(schedule, command.into_command_configs())
+    }
+}
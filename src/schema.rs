@@ -0,0 +1,228 @@
+use crate::registry::{CommandKind, CommandMeta, CommandRegistry};
+use bevy_ecs::entity::Entity;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// Produces the JSON schema of args for commands generated with `#[command(schema)]`
+pub type SchemaFn = fn() -> Value;
+
+/// Implemented for commands generated with `#[command(schema)]`
+pub trait CommandSchema {
+    /// The JSON schema describing this command's serialized fields
+    fn json_schema() -> Value;
+}
+
+/// A type which can describe its serialized form as a JSON schema
+pub trait JsonSchema {
+    fn json_schema() -> Value;
+
+    /// Whether a field of this type may be omitted when deserializing
+    fn optional() -> bool {
+        false
+    }
+}
+
+macro_rules! impl_json_schema {
+    ($schema:tt => $($ty:ty),*) => {
+        $(
+            impl JsonSchema for $ty {
+                fn json_schema() -> Value {
+                    json!($schema)
+                }
+            }
+        )*
+    };
+}
+
+impl_json_schema!({ "type": "integer", "minimum": 0 } => u8, u16, u32, u64, u128, usize);
+impl_json_schema!({ "type": "integer" } => i8, i16, i32, i64, i128, isize);
+impl_json_schema!({ "type": "number" } => f32, f64);
+impl_json_schema!({ "type": "boolean" } => bool);
+impl_json_schema!({ "type": "string" } => String, &'static str);
+impl_json_schema!({ "type": "string", "minLength": 1, "maxLength": 1 } => char);
+impl_json_schema!({ "type": "null" } => ());
+impl_json_schema!({ "type": "integer", "minimum": 0, "description": "Entity::to_bits" } => Entity);
+
+impl<T> JsonSchema for PhantomData<T> {
+    fn json_schema() -> Value {
+        json!({ "type": "null" })
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Option<T> {
+    fn json_schema() -> Value {
+        json!({ "anyOf": [T::json_schema(), { "type": "null" }] })
+    }
+
+    fn optional() -> bool {
+        true
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Vec<T> {
+    fn json_schema() -> Value {
+        json!({ "type": "array", "items": T::json_schema() })
+    }
+}
+
+impl<T: JsonSchema, const N: usize> JsonSchema for [T; N] {
+    fn json_schema() -> Value {
+        json!({ "type": "array", "items": T::json_schema(), "minItems": N, "maxItems": N })
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for HashMap<String, T> {
+    fn json_schema() -> Value {
+        json!({ "type": "object", "additionalProperties": T::json_schema() })
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for BTreeMap<String, T> {
+    fn json_schema() -> Value {
+        json!({ "type": "object", "additionalProperties": T::json_schema() })
+    }
+}
+
+macro_rules! impl_json_schema_tuple {
+    ($($name:ident),*) => {
+        impl<$($name: JsonSchema),*> JsonSchema for ($($name,)*) {
+            fn json_schema() -> Value {
+                let items = [$($name::json_schema()),*];
+                json!({
+                    "type": "array",
+                    "prefixItems": items,
+                    "minItems": items.len(),
+                    "maxItems": items.len(),
+                })
+            }
+        }
+    };
+}
+
+impl_json_schema_tuple!(A);
+impl_json_schema_tuple!(A, B);
+impl_json_schema_tuple!(A, B, C);
+impl_json_schema_tuple!(A, B, C, D);
+
+/// Serializes a field's default value, or `null` if it cannot be serialized
+pub fn to_value<T: Serialize>(value: T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+/// Builds the object schema of a command's fields, used by generated `CommandSchema` impls
+pub struct ObjectSchema {
+    schema: Map<String, Value>,
+    properties: Map<String, Value>,
+    required: Vec<Value>,
+}
+
+impl ObjectSchema {
+    pub fn new<T: CommandMeta>() -> Self {
+        let mut schema = Map::new();
+        schema.insert("title".into(), T::INFO.struct_name.into());
+        if !T::INFO.docs.is_empty() {
+            schema.insert("description".into(), T::INFO.docs.into());
+        }
+        schema.insert("type".into(), "object".into());
+
+        Self {
+            schema,
+            properties: Map::new(),
+            required: Vec::new(),
+        }
+    }
+
+    /// Adds a field, which is required unless it has a default, is optional, or `required` is false
+    pub fn field<T: JsonSchema>(
+        &mut self,
+        name: &str,
+        docs: &str,
+        default: Option<Value>,
+        required: bool,
+    ) {
+        let mut schema = T::json_schema();
+        if let Value::Object(schema) = &mut schema {
+            if !docs.is_empty() {
+                schema.insert("description".into(), docs.into());
+            }
+            if let Some(default) = &default {
+                schema.insert("default".into(), default.clone());
+            }
+        }
+        if required && default.is_none() && !T::optional() {
+            self.required.push(name.into());
+        }
+        self.properties.insert(name.into(), schema);
+    }
+
+    pub fn finish(mut self) -> Value {
+        self.schema
+            .insert("properties".into(), Value::Object(self.properties));
+        self.schema
+            .insert("required".into(), Value::Array(self.required));
+        self.schema
+            .insert("additionalProperties".into(), false.into());
+        Value::Object(self.schema)
+    }
+}
+
+impl CommandRegistry {
+    /// A JSON schema validating a `CommandRequest` for any registered command with a schema
+    pub fn json_schema(&self) -> Value {
+        let commands = self
+            .iter()
+            .filter_map(|registration| {
+                let schema = registration.schema()?;
+                let info = registration.info();
+
+                let mut properties = Map::new();
+                properties.insert("command".into(), json!({ "const": info.name }));
+                let mut required = vec![Value::from("command")];
+                if info.kind == CommandKind::EntityCommand {
+                    properties.insert(
+                        "entity".into(),
+                        json!({
+                            "anyOf": [Entity::json_schema(), { "type": "string", "pattern": "^[0-9]+(v[0-9]+)?$" }],
+                        }),
+                    );
+                    required.push("entity".into());
+                }
+                properties.insert("args".into(), schema());
+                properties.insert("command_id".into(), json!({ "const": info.id }));
+                properties.insert(
+                    "version".into(),
+                    json!({ "type": "integer", "minimum": 1, "maximum": info.version }),
+                );
+                if !info.params.is_empty() {
+                    required.push("args".into());
+                }
+
+                let mut command = Map::new();
+                command.insert("title".into(), info.name.into());
+                if !info.docs.is_empty() {
+                    command.insert("description".into(), info.docs.into());
+                }
+                command.insert("type".into(), "object".into());
+                command.insert("properties".into(), Value::Object(properties));
+                command.insert("required".into(), Value::Array(required));
+                Some(Value::Object(command))
+            })
+            .collect::<Vec<_>>();
+
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "CommandRequest",
+            "oneOf": commands,
+        })
+    }
+
+    /// Writes [`CommandRegistry::json_schema`] to a file
+    pub fn write_json_schema(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let schema = serde_json::to_string_pretty(&self.json_schema())?;
+        std::fs::write(path, schema)
+    }
+}
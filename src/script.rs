@@ -0,0 +1,209 @@
+use crate::json::{CommandRequest, RunJsonCommandExt};
+use crate::registry::CommandRegistry;
+use bevy_app::{App, Plugin, Update};
+use bevy_asset::io::Reader;
+use bevy_asset::{Asset, AssetApp, AssetLoader, Assets, AsyncReadExt, Handle, LoadContext};
+use bevy_ecs::prelude::*;
+use bevy_reflect::TypePath;
+use bevy_time::Time;
+use bevy_utils::tracing::warn;
+use bevy_utils::BoxedFuture;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+
+/// A single step of a [`CommandScript`]
+///
+/// eg. `{ "command": "spawn_enemy", "args": { "level": 2 } }`, `{ "wait": 1.5 }` or `{ "wait_frames": 2 }`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum ScriptStep {
+    /// Waits for a number of seconds before continuing
+    Wait {
+        wait: f32,
+    },
+    /// Waits for a number of frames before continuing
+    WaitFrames {
+        wait_frames: u32,
+    },
+    Command(CommandRequest),
+}
+
+/// A list of serialized commands, loaded from `.commands.ron` or `.commands.json` files
+///
+/// Commands must be generated with `#[command(serde)]` and registered to be run
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(transparent)]
+pub struct CommandScript {
+    pub steps: Vec<ScriptStep>,
+}
+
+impl CommandScript {
+    pub fn from_json(json: &[u8]) -> Result<Self, ScriptError> {
+        serde_json::from_slice(json).map_err(ScriptError::Json)
+    }
+
+    pub fn from_ron(ron: &[u8]) -> Result<Self, ScriptError> {
+        ron::de::from_bytes(ron).map_err(ScriptError::Ron)
+    }
+
+    /// Applies every command immediately, ignoring any waits
+    ///
+    /// Stops at the first command that fails to run
+    pub fn run(&self, world: &mut World) -> Result<(), crate::json::JsonError> {
+        for step in &self.steps {
+            if let ScriptStep::Command(request) = step {
+                world.run_json_command(request.clone())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Errors that may occur while loading a [`CommandScript`]
+#[derive(Debug)]
+pub enum ScriptError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::Io(err) => write!(f, "failed to read script: {err}"),
+            ScriptError::Json(err) => write!(f, "invalid script: {err}"),
+            ScriptError::Ron(err) => write!(f, "invalid script: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// Loads [`CommandScript`]s from `.commands.ron` and `.commands.json` files
+#[derive(Default)]
+pub struct CommandScriptLoader;
+
+impl AssetLoader for CommandScriptLoader {
+    type Asset = CommandScript;
+    type Settings = ();
+    type Error = ScriptError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader
+                .read_to_end(&mut bytes)
+                .await
+                .map_err(ScriptError::Io)?;
+            let is_json = load_context
+                .path()
+                .extension()
+                .is_some_and(|extension| extension == "json");
+            if is_json {
+                CommandScript::from_json(&bytes)
+            } else {
+                CommandScript::from_ron(&bytes)
+            }
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["commands.ron", "commands.json"]
+    }
+}
+
+/// Runs a [`CommandScript`] one step at a time, waiting where the script asks us to
+///
+/// Removed from its entity once the script has finished
+#[derive(Component, Clone, Debug)]
+pub struct ScriptRunner {
+    script: Handle<CommandScript>,
+    step: usize,
+    wait: f32,
+    wait_frames: u32,
+}
+
+impl ScriptRunner {
+    /// The script will start once it has loaded
+    pub fn new(script: Handle<CommandScript>) -> Self {
+        Self {
+            script,
+            step: 0,
+            wait: 0.0,
+            wait_frames: 0,
+        }
+    }
+
+    /// The index of the next step to run
+    pub fn step(&self) -> usize {
+        self.step
+    }
+}
+
+/// Loads [`CommandScript`] assets, and runs any [`ScriptRunner`]s each frame
+///
+/// Requires bevy's `AssetPlugin`
+pub struct CommandScriptPlugin;
+
+impl Plugin for CommandScriptPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CommandRegistry>()
+            .init_asset::<CommandScript>()
+            .init_asset_loader::<CommandScriptLoader>()
+            .add_systems(Update, run_command_scripts);
+    }
+}
+
+fn run_command_scripts(world: &mut World) {
+    let runners = world
+        .query_filtered::<Entity, With<ScriptRunner>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    let delta = world
+        .get_resource::<Time>()
+        .map_or(0.0, |time| time.delta_seconds());
+
+    for entity in runners {
+        if let Some(mut runner) = world.get_mut::<ScriptRunner>(entity) {
+            runner.wait -= delta;
+            runner.wait_frames = runner.wait_frames.saturating_sub(1);
+        }
+
+        // the runner may have been removed by one of our own commands
+        while let Some(runner) = world.get::<ScriptRunner>(entity) {
+            if runner.wait > 0.0 || runner.wait_frames > 0 {
+                break;
+            }
+            let Some(script) = world
+                .resource::<Assets<CommandScript>>()
+                .get(&runner.script)
+            else {
+                // not loaded yet
+                break;
+            };
+            let Some(step) = script.steps.get(runner.step).cloned() else {
+                world.entity_mut(entity).remove::<ScriptRunner>();
+                break;
+            };
+
+            let mut runner = world.get_mut::<ScriptRunner>(entity).unwrap();
+            runner.step += 1;
+            match step {
+                ScriptStep::Wait { wait } => runner.wait = wait,
+                ScriptStep::WaitFrames { wait_frames } => runner.wait_frames = wait_frames,
+                ScriptStep::Command(request) => {
+                    let name = request.command.clone();
+                    if let Err(err) = world.run_json_command(request) {
+                        warn!("failed to run script command `{name}`: {err}");
+                    }
+                }
+            }
+        }
+    }
+}
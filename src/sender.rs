@@ -0,0 +1,110 @@
+use bevy_app::{App, First, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::{Command, EntityCommand};
+use bevy_utils::synccell::SyncCell;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+type SentCommand = Box<dyn FnOnce(&mut World) + Send>;
+
+/// A cloneable handle for issuing commands from other threads, eg. networking or file watchers
+///
+/// Every generated `Commands` method is also available on the sender, eg. `sender.foo(args)`
+///
+/// Commands are applied at the start of each frame by the [`CommandSenderPlugin`], in the order they were sent
+#[derive(Resource, Clone)]
+pub struct CommandSender {
+    sender: Sender<SentCommand>,
+}
+
+impl CommandSender {
+    /// A sender, and the receiver its commands should be applied from
+    pub fn new() -> (Self, CommandReceiver) {
+        let (sender, receiver) = mpsc::channel();
+        (
+            Self { sender },
+            CommandReceiver {
+                receiver: SyncCell::new(receiver),
+            },
+        )
+    }
+
+    /// Sends a command to be applied, silently dropping it if the world no longer exists
+    pub fn add(&mut self, command: impl Command) {
+        let _ = self
+            .sender
+            .send(Box::new(move |world: &mut World| command.apply(world)));
+    }
+
+    /// Sends `request` to be applied, and returns a channel receiving its result
+    ///
+    /// The channel is disconnected without a result if the world no longer exists
+    pub fn request<R: Send + 'static>(
+        &mut self,
+        request: impl FnOnce(&mut World) -> R + Send + 'static,
+    ) -> Receiver<R> {
+        let (reply, receiver) = mpsc::sync_channel(1);
+        self.add(move |world: &mut World| {
+            let _ = reply.send(request(world));
+        });
+        receiver
+    }
+
+    /// Issues entity commands for `entity`, eg. `sender.entity(entity).foo(args)`
+    pub fn entity(&mut self, entity: Entity) -> EntityCommandSender<'_> {
+        EntityCommandSender {
+            sender: self,
+            entity,
+        }
+    }
+}
+
+/// Issues entity commands through a [`CommandSender`]
+pub struct EntityCommandSender<'a> {
+    sender: &'a mut CommandSender,
+    entity: Entity,
+}
+
+impl EntityCommandSender<'_> {
+    pub fn id(&self) -> Entity {
+        self.entity
+    }
+
+    /// Sends an entity command to be applied to our entity
+    pub fn add<M: 'static>(&mut self, command: impl EntityCommand<M>) -> &mut Self {
+        self.sender.add(command.with_entity(self.entity));
+        self
+    }
+}
+
+/// Receives commands sent by each clone of a [`CommandSender`]
+#[derive(Resource)]
+pub struct CommandReceiver {
+    receiver: SyncCell<Receiver<SentCommand>>,
+}
+
+impl CommandReceiver {
+    /// Applies every command received so far
+    pub fn apply(&mut self, world: &mut World) {
+        while let Ok(command) = self.receiver.get().try_recv() {
+            command(world);
+        }
+    }
+}
+
+/// Inserts a [`CommandSender`] resource, and applies its commands in `First`
+///
+/// Clone the resource to send commands from other threads
+pub struct CommandSenderPlugin;
+
+impl Plugin for CommandSenderPlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = CommandSender::new();
+        app.insert_resource(sender)
+            .insert_resource(receiver)
+            .add_systems(First, apply_sent_commands);
+    }
+}
+
+fn apply_sent_commands(world: &mut World) {
+    world.resource_scope(|world, mut receiver: Mut<CommandReceiver>| receiver.apply(world));
+}
@@ -0,0 +1,89 @@
+use bevy_app::{App, PreUpdate};
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::{Command, EntityCommand};
+use bevy_ui::{Interaction, UiSystem};
+
+/// Queues a clone of its command each time the button is pressed
+///
+/// Each command type must be registered via [`RegisterCommandButtonExt::register_command_button`]
+#[derive(Component, Clone, Debug)]
+pub struct CommandButton<C: Command + Clone + Sync> {
+    pub command: C,
+}
+
+impl<C: Command + Clone + Sync> CommandButton<C> {
+    pub fn new(command: C) -> Self {
+        Self { command }
+    }
+}
+
+/// Queues a clone of its entity command each time the button is pressed
+///
+/// The command is applied to the button itself, unless a target is given via [`EntityCommandButton::with_target`], and is skipped if the target was despawned
+///
+/// Each command type must be registered via [`RegisterCommandButtonExt::register_entity_command_button`]
+#[derive(Component, Clone, Debug)]
+pub struct EntityCommandButton<C: EntityCommand + Clone + Sync> {
+    pub command: C,
+    pub target: Option<Entity>,
+}
+
+impl<C: EntityCommand + Clone + Sync> EntityCommandButton<C> {
+    pub fn new(command: C) -> Self {
+        Self {
+            command,
+            target: None,
+        }
+    }
+
+    /// Applies the command to `target` rather than the button
+    pub fn with_target(mut self, target: Entity) -> Self {
+        self.target = Some(target);
+        self
+    }
+}
+
+/// Registers systems issuing the commands of pressed buttons, in `PreUpdate` after bevy updates each `Interaction`
+pub trait RegisterCommandButtonExt {
+    fn register_command_button<C: Command + Clone + Sync>(&mut self) -> &mut Self;
+
+    fn register_entity_command_button<C: EntityCommand + Clone + Sync>(&mut self) -> &mut Self;
+}
+
+impl RegisterCommandButtonExt for App {
+    fn register_command_button<C: Command + Clone + Sync>(&mut self) -> &mut Self {
+        self.add_systems(PreUpdate, press_command_buttons::<C>.after(UiSystem::Focus))
+    }
+
+    fn register_entity_command_button<C: EntityCommand + Clone + Sync>(&mut self) -> &mut Self {
+        self.add_systems(
+            PreUpdate,
+            press_entity_command_buttons::<C>.after(UiSystem::Focus),
+        )
+    }
+}
+
+fn press_command_buttons<C: Command + Clone + Sync>(
+    buttons: Query<(&Interaction, &CommandButton<C>), Changed<Interaction>>,
+    mut commands: Commands,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Pressed {
+            commands.add(button.command.clone());
+        }
+    }
+}
+
+fn press_entity_command_buttons<C: EntityCommand + Clone + Sync>(
+    buttons: Query<(Entity, &Interaction, &EntityCommandButton<C>), Changed<Interaction>>,
+    mut commands: Commands,
+) {
+    for (entity, interaction, button) in &buttons {
+        if *interaction == Interaction::Pressed {
+            let target = button.target.unwrap_or(entity);
+            if let Some(mut target) = commands.get_entity(target) {
+                target.add(button.command.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use crate::console::DynCommand;
+use bevy_ecs::prelude::*;
+use bevy_utils::synccell::SyncCell;
+use std::cell::Cell;
+
+thread_local! {
+    /// How many undoable commands are currently being applied on this thread
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+    /// Whether an undo step is currently being reverted on this thread, so that its commands are not recorded again
+    static UNDOING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Reverts a single command, and applies it again when redone
+struct UndoEntry {
+    undo: SyncCell<DynCommand>,
+    redo: SyncCell<DynCommand>,
+}
+
+/// One or more commands undone together
+struct UndoStep {
+    label: String,
+    entries: Vec<UndoEntry>,
+}
+
+/// Commands to apply again, in the order they were originally applied
+struct RedoStep {
+    label: String,
+    commands: Vec<SyncCell<DynCommand>>,
+}
+
+/// Records the inverse of every `#[command(undo = T)]` command as it is applied
+///
+/// Commands applied from within another undoable command are not recorded, since undoing the outer command should revert them too
+#[derive(Resource, Default)]
+pub struct UndoHistory {
+    undo: Vec<UndoStep>,
+    redo: Vec<RedoStep>,
+    group: Option<UndoStep>,
+    group_depth: usize,
+    limit: Option<usize>,
+}
+
+impl UndoHistory {
+    /// Only keeps the last `limit` undo steps
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            ..Default::default()
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// The label of the step that would be undone next
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.last().map(|step| step.label.as_str())
+    }
+
+    /// The label of the step that would be redone next
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|step| step.label.as_str())
+    }
+
+    /// Labels of every step that may be undone, most recent first
+    pub fn undo_labels(&self) -> impl Iterator<Item = &str> {
+        self.undo.iter().rev().map(|step| step.label.as_str())
+    }
+
+    /// Labels of every step that may be redone, most recent first
+    pub fn redo_labels(&self) -> impl Iterator<Item = &str> {
+        self.redo.iter().rev().map(|step| step.label.as_str())
+    }
+
+    /// Combines every command recorded until [`UndoHistory::end_group`] into a single undo step
+    ///
+    /// Groups may be nested, in which case the outermost label is used
+    pub fn begin_group(&mut self, label: impl Into<String>) {
+        if self.group_depth == 0 {
+            self.group = Some(UndoStep {
+                label: label.into(),
+                entries: Vec::new(),
+            });
+        }
+        self.group_depth += 1;
+    }
+
+    pub fn end_group(&mut self) {
+        self.group_depth = self.group_depth.saturating_sub(1);
+        if self.group_depth == 0 {
+            if let Some(group) = self.group.take() {
+                self.push_step(group, true);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    fn push(&mut self, label: &str, entry: UndoEntry) {
+        match &mut self.group {
+            Some(group) => group.entries.push(entry),
+            None => self.push_step(
+                UndoStep {
+                    label: label.to_string(),
+                    entries: vec![entry],
+                },
+                true,
+            ),
+        }
+    }
+
+    /// New steps invalidate anything that was undone, but redone steps do not
+    fn push_step(&mut self, step: UndoStep, clear_redo: bool) {
+        if step.entries.is_empty() {
+            return;
+        }
+        if clear_redo {
+            self.redo.clear();
+        }
+        self.undo.push(step);
+        if let Some(limit) = self.limit {
+            if self.undo.len() > limit {
+                self.undo.drain(..self.undo.len() - limit);
+            }
+        }
+    }
+
+    /// Closes any open group, so that it may be undone
+    fn close_group(&mut self) {
+        self.group_depth = 0;
+        if let Some(group) = self.group.take() {
+            self.push_step(group, true);
+        }
+    }
+}
+
+/// Marks an undoable command as being applied, until dropped
+pub struct UndoGuard(());
+
+impl Drop for UndoGuard {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// Called by generated `apply` impls to record the inverse of the command being applied
+///
+/// `undo` is called before the command is applied, so that it may capture whatever state the command will change
+pub fn record(
+    world: &mut World,
+    label: &str,
+    redo: impl FnOnce() -> DynCommand,
+    undo: impl FnOnce(&mut World) -> DynCommand,
+) -> UndoGuard {
+    let depth = DEPTH.with(|depth| depth.replace(depth.get() + 1));
+    let guard = UndoGuard(());
+    if depth == 0 && !UNDOING.with(Cell::get) && world.contains_resource::<UndoHistory>() {
+        let entry = UndoEntry {
+            undo: SyncCell::new(undo(world)),
+            redo: SyncCell::new(redo()),
+        };
+        world.resource_mut::<UndoHistory>().push(label, entry);
+    }
+    guard
+}
+
+/// Marks an undo step as being reverted, until dropped
+struct UndoingGuard(bool);
+
+impl UndoingGuard {
+    fn new() -> Self {
+        Self(UNDOING.with(|undoing| undoing.replace(true)))
+    }
+}
+
+impl Drop for UndoingGuard {
+    fn drop(&mut self) {
+        UNDOING.with(|undoing| undoing.set(self.0));
+    }
+}
+
+fn undo(world: &mut World) {
+    let Some(mut history) = world.get_resource_mut::<UndoHistory>() else {
+        return;
+    };
+    history.close_group();
+    let Some(step) = history.undo.pop() else {
+        return;
+    };
+
+    let undoing = UndoingGuard::new();
+    let mut commands = Vec::with_capacity(step.entries.len());
+    for entry in step.entries.into_iter().rev() {
+        SyncCell::to_inner(entry.undo)(world);
+        commands.push(entry.redo);
+    }
+    commands.reverse();
+    drop(undoing);
+
+    world.resource_mut::<UndoHistory>().redo.push(RedoStep {
+        label: step.label,
+        commands,
+    });
+}
+
+fn redo(world: &mut World) {
+    let Some(mut history) = world.get_resource_mut::<UndoHistory>() else {
+        return;
+    };
+    history.close_group();
+    let Some(step) = history.redo.pop() else {
+        return;
+    };
+
+    // our commands record their inverses again, since the world may have changed since
+    history.begin_group(step.label);
+    for command in step.commands {
+        SyncCell::to_inner(command)(world);
+    }
+
+    let mut history = world.resource_mut::<UndoHistory>();
+    history.group_depth = 0;
+    if let Some(group) = history.group.take() {
+        history.push_step(group, false);
+    }
+}
+
+/// Undoes and redoes steps of the [`UndoHistory`], if present
+pub trait UndoExt {
+    /// Reverts the most recent undo step
+    fn undo(&mut self);
+
+    /// Applies the most recently undone step again
+    fn redo(&mut self);
+
+    /// See [`UndoHistory::begin_group`]
+    fn begin_undo_group(&mut self, label: impl Into<String>);
+
+    fn end_undo_group(&mut self);
+}
+
+impl UndoExt for World {
+    fn undo(&mut self) {
+        undo(self);
+    }
+
+    fn redo(&mut self) {
+        redo(self);
+    }
+
+    fn begin_undo_group(&mut self, label: impl Into<String>) {
+        if let Some(mut history) = self.get_resource_mut::<UndoHistory>() {
+            history.begin_group(label);
+        }
+    }
+
+    fn end_undo_group(&mut self) {
+        if let Some(mut history) = self.get_resource_mut::<UndoHistory>() {
+            history.end_group();
+        }
+    }
+}
+
+impl UndoExt for Commands<'_, '_> {
+    fn undo(&mut self) {
+        self.add(undo);
+    }
+
+    fn redo(&mut self) {
+        self.add(redo);
+    }
+
+    fn begin_undo_group(&mut self, label: impl Into<String>) {
+        let label = label.into();
+        self.add(move |world: &mut World| world.begin_undo_group(label));
+    }
+
+    fn end_undo_group(&mut self) {
+        self.add(|world: &mut World| world.end_undo_group());
+    }
+}
@@ -1,4 +1,29 @@
+// not every test uses every helper
+#![allow(dead_code)]
+
+use bevy::app::Plugins;
 use bevy::prelude::*;
 
 #[derive(Resource, Component, Debug, Deref, DerefMut)]
 pub struct TestUsize(pub usize);
+
+/// Records the order commands were applied in
+#[derive(Resource, Default)]
+pub struct Log(pub Vec<String>);
+
+/// An app with the given plugins and an empty `Log`
+pub fn log_app<M>(plugins: impl Plugins<M>) -> App {
+    let mut app = App::new();
+    app.add_plugins(plugins).init_resource::<Log>();
+    app
+}
+
+/// Takes the entries logged so far
+pub fn take_log(app: &mut App) -> Vec<String> {
+    std::mem::take(&mut app.world.resource_mut::<Log>().0)
+}
+
+/// The `TestUsize` resource's value
+pub fn value(app: &App) -> usize {
+    **app.world.resource::<TestUsize>()
+}
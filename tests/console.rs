@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+use bevy_commandify::console::{tokenize, usage};
+use bevy_commandify::*;
+
+mod common;
+use common::TestUsize;
+
+#[derive(Component, Debug, PartialEq)]
+struct Enemy {
+    x: f32,
+    y: f32,
+    name: String,
+}
+
+/// Spawns an enemy at the given position
+#[command(console)]
+fn spawn_enemy(world: &mut World, x: f32, y: f32, name: String) {
+    world.spawn(Enemy { x, y, name });
+}
+
+#[command(console)]
+fn sub(In(n): In<usize>, mut m: ResMut<TestUsize>) {
+    **m -= n;
+}
+
+#[entity_command(console)]
+fn heal(world: &mut World, entity: Entity, amount: usize) {
+    **world.get_mut::<TestUsize>(entity).unwrap() += amount;
+}
+
+#[command]
+fn hidden(_world: &mut World) {}
+
+fn setup() -> World {
+    let mut world = World::new();
+    world
+        .register_command::<SpawnEnemyCommand>()
+        .register_command::<SubCommand>()
+        .register_command::<HealEntityCommand>()
+        .register_command::<HiddenCommand>();
+    world
+}
+
+/// Arguments should be parsed into the command's fields and applied
+#[test]
+fn run_command_line() {
+    let mut world = setup();
+    world.insert_resource(TestUsize(10));
+    let entity = world.spawn(TestUsize(0)).id();
+
+    world
+        .run_command_line(r#"spawn_enemy 10 20.5 "orc chief""#)
+        .unwrap();
+    world.run_command_line("sub 4").unwrap();
+    world
+        .run_command_line(&format!("heal {entity:?} 3"))
+        .unwrap();
+    world
+        .run_command_line(&format!("heal {} 2", entity.index()))
+        .unwrap();
+
+    let enemy = world.query::<&Enemy>().single(&world);
+    assert_eq!(
+        enemy,
+        &Enemy {
+            x: 10.0,
+            y: 20.5,
+            name: "orc chief".to_string()
+        }
+    );
+    assert_eq!(**world.resource::<TestUsize>(), 6);
+    assert_eq!(**world.get::<TestUsize>(entity).unwrap(), 5);
+}
+
+/// Bad input should be reported along with the command's usage
+#[test]
+fn errors() {
+    let mut world = setup();
+
+    assert_eq!(world.run_command_line("   "), Err(ConsoleError::Empty));
+    assert_eq!(
+        world.run_command_line("nope"),
+        Err(ConsoleError::UnknownCommand("nope".into()))
+    );
+    assert_eq!(
+        world.run_command_line("hidden"),
+        Err(ConsoleError::Unsupported("hidden".into()))
+    );
+    assert_eq!(
+        world.run_command_line("spawn_enemy 1 \"orc"),
+        Err(ConsoleError::UnterminatedQuote)
+    );
+
+    let usage =
+        "spawn_enemy <x: f32> <y: f32> <name: String>\n\nSpawns an enemy at the given position";
+    assert_eq!(
+        world.run_command_line("spawn_enemy 1 2"),
+        Err(ConsoleError::WrongArgCount {
+            expected: 3,
+            found: 2,
+            usage: usage.into(),
+        })
+    );
+    assert_eq!(
+        world.run_command_line("spawn_enemy 1 2 orc goblin"),
+        Err(ConsoleError::WrongArgCount {
+            expected: 3,
+            found: 4,
+            usage: usage.into(),
+        })
+    );
+    let Err(ConsoleError::InvalidArg { param, value, .. }) =
+        world.run_command_line("spawn_enemy 1 two orc")
+    else {
+        panic!("expected an invalid argument");
+    };
+    assert_eq!((param, value.as_str()), ("y", "two"));
+
+    // nothing should have been applied
+    assert_eq!(world.query::<&Enemy>().iter(&world).count(), 0);
+}
+
+#[test]
+fn tokenizer() {
+    assert_eq!(
+        tokenize(r#"  say "hello \"world\"" now "" "#).unwrap(),
+        ["say", r#"hello "world""#, "now", ""]
+    );
+}
+
+#[test]
+fn usage_text() {
+    assert_eq!(
+        usage(&HealEntityCommand::INFO),
+        "heal <entity: Entity> <amount: usize>"
+    );
+}
+
+/// Only console commands should be offered as completions
+#[test]
+fn completions() {
+    let world = setup();
+    let registry = world.resource::<CommandRegistry>();
+
+    assert_eq!(registry.completions("s"), ["spawn_enemy", "sub"]);
+    assert_eq!(registry.completions("h"), ["heal"]);
+    assert!(registry.completions("heal 1").is_empty());
+}
+
+/// Lines pushed into `ConsoleInput` should run on the next update
+#[test]
+fn console_plugin() {
+    let mut app = App::new();
+    app.add_plugins(ConsolePlugin)
+        .register_command::<SubCommand>()
+        .insert_resource(TestUsize(10));
+
+    let sender = app.world.resource::<ConsoleInput>().sender();
+    sender.send("sub 3".into()).unwrap();
+    app.world.resource::<ConsoleInput>().push("sub 2");
+    app.update();
+
+    assert_eq!(**app.world.resource::<TestUsize>(), 5);
+}
@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+use bevy_commandify::*;
+
+mod common;
+use common::{log_app, take_log, Log, TestUsize};
+
+#[command(unique)]
+fn save(world: &mut World) {
+    world.resource_mut::<Log>().0.push("save".into());
+}
+
+/// Runs a system of its own, which should not start a new flush
+#[command(unique)]
+fn autosave(mut log: ResMut<Log>) {
+    log.0.push("autosave".into());
+}
+
+/// Runs a system of its own between `unique` commands
+#[command]
+fn note(mut log: ResMut<Log>) {
+    log.0.push("note".into());
+}
+
+#[command(key = name)]
+fn greet(world: &mut World, name: String) {
+    world.resource_mut::<Log>().0.push(format!("hello {name}"));
+}
+
+#[command(unique = last)]
+fn set_volume(world: &mut World, volume: usize) {
+    world
+        .resource_mut::<Log>()
+        .0
+        .push(format!("volume {volume}"));
+}
+
+#[command(once)]
+fn init(world: &mut World) {
+    world.resource_mut::<Log>().0.push("init".into());
+}
+
+#[command(key = channel)]
+fn notify(world: &mut World, channel: usize, message: String) {
+    world
+        .resource_mut::<Log>()
+        .0
+        .push(format!("{channel}: {message}"));
+}
+
+#[command(once, key = level)]
+fn unlock(world: &mut World, level: usize) {
+    world
+        .resource_mut::<Log>()
+        .0
+        .push(format!("unlock {level}"));
+}
+
+#[entity_command(unique = last)]
+fn set_health(world: &mut World, entity: Entity, health: usize) {
+    **world.get_mut::<TestUsize>(entity).unwrap() = health;
+}
+
+#[test]
+fn unique_first() {
+    let mut app = log_app(CommandDedupPlugin);
+    app.add_systems(Update, |mut commands: Commands| {
+        commands.save();
+        commands.notify(1, "a".into());
+        commands.save();
+        commands.notify(2, "b".into());
+        commands.notify(1, "c".into());
+    });
+
+    app.update();
+    assert_eq!(take_log(&mut app), ["save", "1: a", "2: b"]);
+
+    // reset each frame
+    app.update();
+    assert_eq!(take_log(&mut app), ["save", "1: a", "2: b"]);
+}
+
+/// Duplicates are dropped until the end of each main schedule, however many systems run meanwhile
+#[test]
+fn per_schedule() {
+    let mut app = log_app(CommandDedupPlugin);
+    let save = |mut commands: Commands| {
+        commands.save();
+        commands.note();
+        commands.autosave();
+        commands.autosave();
+    };
+    app.add_systems(Update, ((save, save), save).chain())
+        .add_systems(PostUpdate, save);
+
+    app.update();
+    assert_eq!(
+        take_log(&mut app),
+        ["save", "note", "autosave", "note", "note", "save", "note", "autosave"]
+    );
+
+    // commands applied directly belong to the next schedule, here `First`
+    app.world.save();
+    app.world.save();
+    assert_eq!(take_log(&mut app), ["save"]);
+    app.update();
+    assert_eq!(
+        take_log(&mut app),
+        ["save", "note", "autosave", "note", "note", "save", "note", "autosave"]
+    );
+}
+
+/// Keys are compared by value, not just by their hash
+#[test]
+fn keyed_by_value() {
+    let mut app = log_app(CommandDedupPlugin);
+    app.add_systems(Update, |mut commands: Commands| {
+        commands.greet("a".into());
+        commands.greet("b".into());
+        commands.greet("a".into());
+    });
+
+    app.update();
+    assert_eq!(take_log(&mut app), ["hello a", "hello b"]);
+}
+
+#[test]
+fn unique_last() {
+    let mut app = log_app(CommandDedupPlugin);
+    let entity = app.world.spawn(TestUsize(0)).id();
+    app.add_systems(Update, move |mut commands: Commands| {
+        commands.set_volume(1);
+        commands.entity(entity).set_health(10);
+        commands.set_volume(2);
+        commands.entity(entity).set_health(20);
+    });
+
+    app.update();
+    assert_eq!(take_log(&mut app), ["volume 2"]);
+    assert_eq!(**app.world.get::<TestUsize>(entity).unwrap(), 20);
+}
+
+#[test]
+fn once() {
+    let mut app = log_app(CommandDedupPlugin);
+    app.add_systems(Update, |mut commands: Commands| {
+        commands.init();
+        commands.unlock(1);
+        commands.unlock(1);
+        commands.unlock(2);
+    });
+
+    app.update();
+    app.update();
+    assert_eq!(take_log(&mut app), ["init", "unlock 1", "unlock 2"]);
+
+    app.world.resource_mut::<OnceCommands>().reset();
+    app.update();
+    assert_eq!(take_log(&mut app), ["init", "unlock 1", "unlock 2"]);
+}
+
+#[test]
+fn without_plugin() {
+    let mut world = World::new();
+    world.init_resource::<Log>();
+
+    // `unique` commands are applied as usual, while `once` commands are still tracked
+    world.save();
+    world.save();
+    world.set_volume(1);
+    world.init();
+    world.init();
+    world.set_volume(2);
+    world.save();
+    assert_eq!(
+        world.resource::<Log>().0,
+        ["save", "save", "volume 1", "init", "volume 2", "save"]
+    );
+}
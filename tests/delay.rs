@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_commandify::*;
+use std::time::Duration;
+
+mod common;
+use common::{value, TestUsize};
+
+#[command(delay)]
+fn plus(world: &mut World, n: usize) {
+    **world.resource_mut::<TestUsize>() += n;
+}
+
+#[command(delay)]
+fn delayed(In(tick): In<usize>, mut value: ResMut<TestUsize>) {
+    **value = tick;
+}
+
+#[entity_command(delay)]
+fn increase(world: &mut World, entity: Entity, n: usize) {
+    **world.get_mut::<TestUsize>(entity).unwrap() += n;
+}
+
+/// Each frame advances `Time` by 100ms
+fn delay_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((bevy::time::TimePlugin, CommandSchedulerPlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )))
+        .insert_resource(TestUsize(0));
+    // the first frame's delta is always zero
+    app.update();
+    app
+}
+
+#[test]
+fn after() {
+    let mut app = delay_app();
+    let token = app.world.plus_after(Duration::from_millis(250), 1);
+
+    app.update();
+    app.update();
+    assert_eq!(value(&app), 0);
+    assert!(!token.is_finished());
+
+    app.update();
+    assert_eq!(value(&app), 1);
+    assert!(token.is_applied());
+
+    app.update();
+    assert_eq!(value(&app), 1);
+    assert!(app.world.resource::<CommandScheduler>().is_empty());
+}
+
+#[test]
+fn at_tick() {
+    let mut app = delay_app();
+    let tick = app.world.resource::<CommandScheduler>().tick();
+
+    app.add_systems(
+        Update,
+        move |mut commands: Commands, mut issued: Local<bool>| {
+            if !*issued {
+                // the field named `tick` is kept, and the scheduled tick renamed
+                commands.delayed_at_tick(tick + 2, 7);
+                *issued = true;
+            }
+        },
+    );
+    app.update();
+    assert_eq!(value(&app), 0);
+    app.update();
+    assert_eq!(value(&app), 7);
+}
+
+#[test]
+fn cancel() {
+    let mut app = delay_app();
+    let token = app.world.plus_after(Duration::from_millis(100), 1);
+    let kept = app.world.plus_after(Duration::from_millis(100), 2);
+
+    token.cancel();
+    app.update();
+    assert_eq!(value(&app), 2);
+    assert!(token.is_cancelled());
+    assert!(kept.is_applied());
+
+    // cancelling after being applied has no effect
+    kept.cancel();
+    assert!(kept.is_applied());
+}
+
+#[test]
+fn entity_despawned() {
+    let mut app = delay_app();
+    let kept = app.world.spawn(TestUsize(0)).id();
+    let despawned = app.world.spawn(TestUsize(0)).id();
+
+    let applied = app
+        .world
+        .entity_mut(kept)
+        .increase_after(Duration::from_millis(300), 3);
+    let cancelled = app
+        .world
+        .entity_mut(despawned)
+        .increase_after(Duration::from_millis(200), 3);
+
+    app.update();
+    app.world.despawn(despawned);
+    app.update();
+    assert!(cancelled.is_cancelled());
+    assert_eq!(app.world.resource::<CommandScheduler>().len(), 1);
+
+    app.update();
+    assert!(applied.is_applied());
+    assert_eq!(**app.world.get::<TestUsize>(kept).unwrap(), 3);
+}
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use bevy_commandify::*;
+
+mod common;
+use common::TestUsize;
+
+#[derive(Component)]
+struct Alive;
+
+#[derive(Component)]
+struct Invulnerable;
+
+#[entity_command(requires(TestUsize, Alive), without(Invulnerable))]
+fn damage(In((entity, n)): In<(Entity, usize)>, mut query: Query<&mut TestUsize>) {
+    // no need to handle a missing component
+    **query.get_mut(entity).unwrap() -= n;
+}
+
+#[entity_command(requires(TestUsize), on_skip = log)]
+fn heal(world: &mut World, entity: Entity, n: usize) {
+    **world.get_mut::<TestUsize>(entity).unwrap() += n;
+}
+
+#[entity_command(without(Invulnerable), on_skip = panic)]
+fn kill(world: &mut World, entity: Entity) {
+    world.entity_mut(entity).remove::<Alive>();
+}
+
+#[test]
+fn requires_without() {
+    let mut world = World::new();
+    let alive = world.spawn((TestUsize(10), Alive)).id();
+    let dead = world.spawn(TestUsize(10)).id();
+    let invulnerable = world.spawn((TestUsize(10), Alive, Invulnerable)).id();
+    let empty = world.spawn_empty().id();
+
+    for entity in [alive, dead, invulnerable, empty] {
+        world.entity_mut(entity).damage(3);
+    }
+    assert_eq!(**world.get::<TestUsize>(alive).unwrap(), 7);
+    assert_eq!(**world.get::<TestUsize>(dead).unwrap(), 10);
+    assert_eq!(**world.get::<TestUsize>(invulnerable).unwrap(), 10);
+
+    world.entity_mut(empty).heal(1);
+    world.entity_mut(dead).heal(1);
+    assert_eq!(**world.get::<TestUsize>(dead).unwrap(), 11);
+}
+
+#[test]
+fn despawned() {
+    let mut world = World::new();
+    let entity = world.spawn((TestUsize(10), Alive)).id();
+    world.despawn(entity);
+
+    // eg. queued before the entity was despawned
+    bevy::ecs::system::EntityCommand::apply(DamageEntityCommand { n: 1 }, entity, &mut world);
+}
+
+#[test]
+#[should_panic(expected = "command `kill` failed")]
+fn on_skip_panic() {
+    let mut world = World::new();
+    let entity = world.spawn((Alive, Invulnerable)).id();
+    world.entity_mut(entity).kill();
+}
@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+use bevy_commandify::*;
+
+mod common;
+use common::TestUsize;
+
+#[command(event)]
+fn add(world: &mut World, n: usize) {
+    **world.resource_mut::<TestUsize>() += n;
+}
+
+#[command(event)]
+fn multiply(In(n): In<usize>, mut value: ResMut<TestUsize>) {
+    **value *= n;
+}
+
+#[derive(Resource, Default)]
+struct Seen(Vec<usize>);
+
+#[test]
+fn event_writer() {
+    let mut app = App::new();
+    app.insert_resource(TestUsize(1))
+        .init_resource::<Seen>()
+        .add_command_event::<AddCommand>()
+        .add_command_event::<MultiplyCommand>()
+        .add_systems(
+            Update,
+            (
+                |mut add: EventWriter<AddCommand>,
+                 mut multiply: EventWriter<MultiplyCommand>,
+                 mut frame: Local<usize>| {
+                    // each command type is applied by its own system, in no particular order
+                    if *frame == 0 {
+                        add.add(2);
+                    } else {
+                        multiply.multiply(3);
+                    }
+                    *frame += 1;
+                },
+                // other systems may still read our commands before they are applied
+                |mut events: EventReader<AddCommand>, mut seen: ResMut<Seen>| {
+                    seen.0.extend(events.read().map(|command| command.n));
+                },
+            )
+                .chain(),
+        );
+
+    app.update();
+    assert_eq!(**app.world.resource::<TestUsize>(), 3);
+    assert_eq!(app.world.resource::<Seen>().0, vec![2]);
+
+    app.update();
+    assert_eq!(**app.world.resource::<TestUsize>(), 9);
+
+    // each command is only applied once
+    app.world.resource_mut::<TestUsize>().0 = 0;
+    app.world.run_schedule(PostUpdate);
+    assert_eq!(**app.world.resource::<TestUsize>(), 0);
+}
+
+#[test]
+fn send_event() {
+    let mut app = App::new();
+    app.insert_resource(TestUsize(0))
+        .add_command_event::<AddCommand>();
+
+    app.world.send_event(AddCommand { n: 1 });
+    app.world.send_event(AddCommand { n: 4 });
+    app.update();
+    assert_eq!(**app.world.resource::<TestUsize>(), 5);
+}
+
+/// Commands are left in place for readers after `PostUpdate`, and those sent from `Last` are applied the next frame
+#[test]
+fn read_in_last() {
+    let mut app = App::new();
+    app.insert_resource(TestUsize(0))
+        .init_resource::<Seen>()
+        .add_command_event::<AddCommand>()
+        .add_systems(Update, |mut add: EventWriter<AddCommand>| {
+            add.add(1);
+        })
+        .add_systems(
+            Last,
+            (
+                |mut add: EventWriter<AddCommand>| {
+                    add.add(10);
+                },
+                |mut events: EventReader<AddCommand>, mut seen: ResMut<Seen>| {
+                    seen.0.extend(events.read().map(|command| command.n));
+                },
+            )
+                .chain(),
+        );
+
+    app.update();
+    assert_eq!(**app.world.resource::<TestUsize>(), 1);
+    assert_eq!(app.world.resource::<Seen>().0, vec![1, 10]);
+
+    app.update();
+    assert_eq!(**app.world.resource::<TestUsize>(), 12);
+    assert_eq!(app.world.resource::<Seen>().0, vec![1, 10, 1, 10]);
+}
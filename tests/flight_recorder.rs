@@ -0,0 +1,188 @@
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+use bevy_commandify::*;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Mutex;
+
+mod common;
+use common::TestUsize;
+
+/// The recorder is global, so tests using it must not run at the same time
+static SERIAL: Mutex<()> = Mutex::new(());
+
+#[command(debug)]
+fn add(world: &mut World, n: usize) {
+    **world.resource_mut::<TestUsize>() += n;
+}
+
+#[command]
+fn add_twice(world: &mut World, n: usize) {
+    world.add(n);
+    world.add(n);
+}
+
+#[entity_command(debug)]
+fn tag(In((entity, value)): In<(Entity, usize)>, mut commands: Commands) {
+    commands.entity(entity).insert(TestUsize(value));
+}
+
+#[command(priority = 1)]
+fn boost(world: &mut World) {
+    **world.resource_mut::<TestUsize>() += 1;
+}
+
+#[command(debug, merge = sum_bonus)]
+fn bonus(world: &mut World, n: usize) {
+    **world.resource_mut::<TestUsize>() += n;
+}
+
+fn sum_bonus(last: &mut BonusCommand, next: BonusCommand) {
+    last.n += next.n;
+}
+
+#[command(unique)]
+fn save(_world: &mut World) {}
+
+#[command]
+fn explode(_world: &mut World) {
+    panic!("boom");
+}
+
+/// Applied commands should be recorded with their args and where they were issued from
+#[test]
+fn records() {
+    let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    FlightRecorder::enable(3);
+    FlightRecorder::clear();
+
+    let mut world = World::new();
+    world.insert_resource(TestUsize(0));
+    let entity = world.spawn_empty().id();
+
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &world);
+    commands.add_twice(5);
+    let line = line!() + 1;
+    commands.entity(entity).tag(7);
+    queue.apply(&mut world);
+
+    assert_eq!(**world.resource::<TestUsize>(), 10);
+
+    let records = FlightRecorder::records();
+    // only the last 3 of 4 commands are kept
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].name, "add");
+    assert_eq!(records[0].args.as_deref(), Some("AddCommand { n: 5 }"));
+    // nested commands are located within the command that issued them
+    assert_eq!(records[0].location.unwrap().file(), file!());
+    assert!(records[0].location.unwrap().line() < line);
+    assert_ne!(records[0].location, records[1].location);
+    assert_eq!(records[2].name, "tag");
+    assert_eq!(records[2].entity, Some(entity));
+    assert_eq!(records[2].location.unwrap().file(), file!());
+    assert_eq!(records[2].location.unwrap().line(), line);
+    assert_eq!(
+        records[2].to_string(),
+        format!(
+            "tag({entity:?}) TagEntityCommand {{ value: 7 }} at {}:{}:29",
+            file!(),
+            line
+        )
+    );
+
+    FlightRecorder::disable();
+    world.add(1);
+    assert_eq!(FlightRecorder::records().len(), 3);
+}
+
+/// Prioritized commands should be recorded once, when they are applied, with where they were issued from
+#[test]
+fn prioritized() {
+    let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    FlightRecorder::enable(8);
+    FlightRecorder::clear();
+
+    let mut app = App::new();
+    app.add_plugins(CommandPriorityPlugin)
+        .insert_resource(TestUsize(0));
+    let line = line!() + 2;
+    app.add_systems(Update, |mut commands: Commands| {
+        commands.boost();
+    });
+    app.update();
+    assert_eq!(**app.world.resource::<TestUsize>(), 1);
+
+    let records = FlightRecorder::records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].name, "boost");
+    assert_eq!(records[0].location.unwrap().file(), file!());
+    assert_eq!(records[0].location.unwrap().line(), line);
+}
+
+/// Merged commands should be recorded once, as the command they were merged into
+#[test]
+fn merged() {
+    let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    FlightRecorder::enable(8);
+    FlightRecorder::clear();
+
+    let mut app = App::new();
+    app.add_plugins(CommandDedupPlugin)
+        .insert_resource(TestUsize(0));
+    let line = line!() + 3;
+    app.add_systems(Update, |mut commands: Commands| {
+        commands.bonus(1);
+        commands.bonus(2);
+    });
+    app.update();
+    assert_eq!(**app.world.resource::<TestUsize>(), 3);
+
+    let records = FlightRecorder::records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].args.as_deref(), Some("BonusCommand { n: 3 }"));
+    // the latest instance merged
+    assert_eq!(records[0].location.unwrap().line(), line);
+}
+
+/// Dropped duplicates should not be recorded
+#[test]
+fn deduplicated() {
+    let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    FlightRecorder::enable(8);
+    FlightRecorder::clear();
+
+    let mut app = App::new();
+    app.add_plugins(CommandDedupPlugin);
+    app.add_systems(Update, |mut commands: Commands| {
+        commands.save();
+        commands.save();
+    });
+    app.update();
+
+    let records = FlightRecorder::records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].name, "save");
+}
+
+/// The plugin should dump its records when a command panics
+#[test]
+fn dump_on_panic() {
+    let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+    let path = std::env::temp_dir().join("bevy_commandify_flight_recorder.txt");
+
+    let mut app = App::new();
+    app.add_plugins(FlightRecorderPlugin::new(8).with_file(&path));
+    FlightRecorder::clear();
+    app.world.insert_resource(TestUsize(0));
+
+    app.world.add(1);
+    let result = catch_unwind(AssertUnwindSafe(|| app.world.explode()));
+    assert!(result.is_err());
+
+    let dump = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(dump.contains("boom"), "{dump}");
+    assert!(dump.contains("last 2 commands applied"), "{dump}");
+    assert!(dump.contains("   0: add AddCommand { n: 1 } at"), "{dump}");
+    assert!(dump.contains("   1: explode at"), "{dump}");
+}
@@ -0,0 +1,142 @@
+#![cfg(feature = "input")]
+
+use bevy::prelude::*;
+use bevy_commandify::*;
+
+mod common;
+use common::TestUsize;
+
+#[command(clone)]
+fn fire(world: &mut World, power: usize) {
+    **world.resource_mut::<TestUsize>() += power;
+}
+
+#[entity_command(clone)]
+fn jump(world: &mut World, entity: Entity, height: usize) {
+    **world.get_mut::<TestUsize>(entity).unwrap() += height;
+}
+
+#[cfg(feature = "serde")]
+#[command(serde, clone)]
+fn reload(world: &mut World, ammo: usize) {
+    **world.resource_mut::<TestUsize>() = ammo;
+}
+
+/// Presses `input` for a single frame, releasing it before the next
+fn tap<T: Copy + Eq + std::hash::Hash + Send + Sync + 'static>(app: &mut App, input: T) {
+    app.world.resource_mut::<ButtonInput<T>>().press(input);
+    app.update();
+    let mut buttons = app.world.resource_mut::<ButtonInput<T>>();
+    buttons.release(input);
+    buttons.clear();
+}
+
+#[test]
+fn bind_command() {
+    let mut app = App::new();
+    app.insert_resource(TestUsize(0))
+        .bind_command(KeyCode::KeyF, FireCommand { power: 3 })
+        .bind_command(MouseButton::Left, FireCommand { power: 1 });
+
+    app.update();
+    assert_eq!(**app.world.resource::<TestUsize>(), 0);
+
+    tap(&mut app, KeyCode::KeyF);
+    assert_eq!(**app.world.resource::<TestUsize>(), 3);
+
+    // held, but no longer just pressed
+    app.update();
+    assert_eq!(**app.world.resource::<TestUsize>(), 3);
+
+    tap(&mut app, MouseButton::Left);
+    assert_eq!(**app.world.resource::<TestUsize>(), 4);
+}
+
+#[test]
+fn triggers() {
+    let mut app = App::new();
+    app.insert_resource(TestUsize(0))
+        .bind_command_with(
+            KeyCode::Space,
+            InputTrigger::Pressed,
+            FireCommand { power: 1 },
+        )
+        .bind_command_with(
+            KeyCode::Space,
+            InputTrigger::JustReleased,
+            FireCommand { power: 10 },
+        );
+
+    app.world
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::Space);
+    app.update();
+    app.world.resource_mut::<ButtonInput<KeyCode>>().clear();
+    app.update();
+    app.update();
+    assert_eq!(**app.world.resource::<TestUsize>(), 3);
+
+    app.world
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .release(KeyCode::Space);
+    app.update();
+    assert_eq!(**app.world.resource::<TestUsize>(), 13);
+}
+
+#[test]
+fn bind_entity_command() {
+    let mut app = App::new();
+    let entity = app.world.spawn(TestUsize(0)).id();
+    app.bind_entity_command(KeyCode::KeyJ, entity, JumpEntityCommand { height: 2 });
+
+    tap(&mut app, KeyCode::KeyJ);
+    tap(&mut app, KeyCode::KeyJ);
+    assert_eq!(**app.world.get::<TestUsize>(entity).unwrap(), 4);
+
+    app.world
+        .resource_mut::<CommandBindings<KeyCode>>()
+        .unbind(KeyCode::KeyJ);
+    tap(&mut app, KeyCode::KeyJ);
+    assert_eq!(**app.world.get::<TestUsize>(entity).unwrap(), 4);
+}
+
+/// Pressing a key bound to a despawned entity should do nothing
+#[test]
+fn bind_despawned_entity() {
+    let mut app = App::new();
+    let entity = app.world.spawn(TestUsize(0)).id();
+    app.bind_entity_command(KeyCode::KeyJ, entity, JumpEntityCommand { height: 2 });
+
+    app.world.despawn(entity);
+    tap(&mut app, KeyCode::KeyJ);
+    assert!(app.world.get_entity(entity).is_none());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn bind_from_config() {
+    let config = r#"[
+        { "input": "KeyR", "command": "reload", "args": { "ammo": 30 } },
+        { "input": "KeyF", "trigger": "just_released", "command": "reload", "args": { "ammo": 0 } }
+    ]"#;
+    let bindings = serde_json::from_str::<Vec<InputBinding<KeyCode>>>(config).unwrap();
+
+    let mut app = App::new();
+    app.insert_resource(TestUsize(0))
+        .register_command::<ReloadCommand>()
+        .bind_commands(bindings);
+
+    tap(&mut app, KeyCode::KeyR);
+    assert_eq!(**app.world.resource::<TestUsize>(), 30);
+
+    app.world
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::KeyF);
+    app.update();
+    assert_eq!(**app.world.resource::<TestUsize>(), 30);
+    app.world
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .release(KeyCode::KeyF);
+    app.update();
+    assert_eq!(**app.world.resource::<TestUsize>(), 0);
+}
@@ -0,0 +1,146 @@
+#![cfg(feature = "serde")]
+
+use bevy::prelude::*;
+use bevy_commandify::*;
+
+mod common;
+use common::TestUsize;
+
+#[command(serde)]
+fn spawn_counter(world: &mut World, n: usize) {
+    world.spawn(TestUsize(n));
+}
+
+#[entity_command(serde)]
+fn bump(In((entity, n)): In<(Entity, usize)>, mut query: Query<&mut TestUsize>) {
+    **query.get_mut(entity).unwrap() += n;
+}
+
+/// Issues a nested command, which should not be journaled separately
+#[command(serde)]
+fn bump_all(world: &mut World, n: usize) {
+    let entities = world
+        .query_filtered::<Entity, With<TestUsize>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    for entity in entities {
+        world.entity_mut(entity).bump(n);
+    }
+}
+
+#[derive(Resource, Default)]
+struct Frame(usize);
+
+/// Issues different commands depending on the frame
+fn script(mut commands: Commands, mut frame: ResMut<Frame>, query: Query<Entity, With<TestUsize>>) {
+    match frame.0 {
+        0 => {
+            commands.spawn_counter(1);
+            commands.spawn_counter(2);
+        }
+        2 => {
+            for entity in &query {
+                commands.entity(entity).bump(10);
+            }
+        }
+        3 => {
+            commands.bump_all(100);
+        }
+        _ => {}
+    }
+    frame.0 += 1;
+}
+
+fn journal_app(plugin: CommandJournalPlugin) -> App {
+    let mut app = App::new();
+    app.add_plugins(plugin)
+        .register_command::<SpawnCounterCommand>()
+        .register_command::<BumpEntityCommand>()
+        .register_command::<BumpAllCommand>();
+    app
+}
+
+fn counters(world: &mut World) -> Vec<usize> {
+    let mut counters = world
+        .query::<&TestUsize>()
+        .iter(world)
+        .map(|n| **n)
+        .collect::<Vec<_>>();
+    counters.sort();
+    counters
+}
+
+/// Commands should be recorded with the tick they were applied on
+#[test]
+fn record() {
+    let mut app = journal_app(CommandJournalPlugin::default());
+    app.init_resource::<Frame>().add_systems(Update, script);
+    for _ in 0..5 {
+        app.update();
+    }
+
+    let journal = app.world.resource::<CommandJournal>();
+    let entries = journal
+        .entries()
+        .iter()
+        .map(|entry| (entry.tick, entry.request.command.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entries,
+        [
+            (0, "spawn_counter"),
+            (0, "spawn_counter"),
+            (2, "bump"),
+            (2, "bump"),
+            (3, "bump_all"),
+        ]
+    );
+    assert_eq!(journal.tick(), 5);
+    assert_eq!(counters(&mut app.world), [111, 112]);
+}
+
+/// Replaying a saved journal into a fresh world should reproduce the same state
+#[test]
+fn replay() {
+    let mut app = journal_app(CommandJournalPlugin::default());
+    app.init_resource::<Frame>().add_systems(Update, script);
+    for _ in 0..5 {
+        app.update();
+    }
+
+    let path = std::env::temp_dir().join("bevy_commandify_journal.jsonl");
+    app.world.resource::<CommandJournal>().save(&path).unwrap();
+    let entries = CommandJournal::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(entries, app.world.resource::<CommandJournal>().entries());
+
+    let mut replay = journal_app(CommandJournalPlugin::replay(entries.clone()));
+    for tick in 0..5 {
+        replay.update();
+        // nothing should be applied ahead of its tick
+        if tick == 2 {
+            assert_eq!(counters(&mut replay.world), [11, 12]);
+        }
+    }
+
+    assert!(replay.world.resource::<JournalReplay>().is_finished());
+    assert_eq!(counters(&mut replay.world), [111, 112]);
+    // the replayed commands are journaled identically
+    assert_eq!(replay.world.resource::<CommandJournal>().entries(), entries);
+}
+
+/// Paused journals should not record anything
+#[test]
+fn pause() {
+    let mut world = World::new();
+    world.init_resource::<CommandJournal>();
+
+    world.resource_mut::<CommandJournal>().pause();
+    world.spawn_counter(1);
+    world.resource_mut::<CommandJournal>().resume();
+    world.spawn_counter(2);
+
+    let journal = world.resource::<CommandJournal>();
+    assert_eq!(journal.entries().len(), 1);
+    assert_eq!(journal.entries()[0].request.args["n"], 2);
+}
@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use bevy_commandify::*;
+
+mod common;
+use common::{log_app, take_log, value, Log, TestUsize};
+
+#[command(merge = sum_score)]
+fn add_score(world: &mut World, n: usize) {
+    **world.resource_mut::<TestUsize>() += n;
+    world.resource_mut::<Log>().0.push(format!("score {n}"));
+}
+
+fn sum_score(last: &mut AddScoreCommand, next: AddScoreCommand) {
+    last.n += next.n;
+}
+
+#[entity_command(merge = sum_damage)]
+fn damage(world: &mut World, entity: Entity, n: usize) {
+    **world.get_mut::<TestUsize>(entity).unwrap() -= n;
+    world.resource_mut::<Log>().0.push(format!("damage {n}"));
+}
+
+fn sum_damage(last: &mut DamageEntityCommand, next: DamageEntityCommand) {
+    last.n += next.n;
+}
+
+#[command(merge = sum_bonus, key = player)]
+fn add_bonus(world: &mut World, player: usize, n: usize) {
+    **world.resource_mut::<TestUsize>() += player * n;
+    world
+        .resource_mut::<Log>()
+        .0
+        .push(format!("bonus {player} {n}"));
+}
+
+fn sum_bonus(last: &mut AddBonusCommand, next: AddBonusCommand) {
+    last.n += next.n;
+}
+
+#[test]
+fn merge() {
+    let mut app = log_app(CommandDedupPlugin);
+    app.insert_resource(TestUsize(0));
+    app.add_systems(Update, |mut commands: Commands| {
+        commands.add_score(1);
+        commands.add_score(2);
+    });
+    app.add_systems(Update, |mut commands: Commands| {
+        commands.add_score(3);
+    });
+
+    app.update();
+    assert_eq!(value(&app), 6);
+    assert_eq!(take_log(&mut app), ["score 6"]);
+
+    app.update();
+    assert_eq!(value(&app), 12);
+    assert_eq!(take_log(&mut app), ["score 6"]);
+}
+
+#[test]
+fn merge_per_entity() {
+    let mut app = log_app(CommandDedupPlugin);
+    app.insert_resource(TestUsize(0));
+    let a = app.world.spawn(TestUsize(10)).id();
+    let b = app.world.spawn(TestUsize(10)).id();
+    app.add_systems(Update, move |mut commands: Commands| {
+        commands.entity(a).damage(1);
+        commands.entity(b).damage(2);
+        commands.entity(a).damage(3);
+    });
+
+    app.update();
+    assert_eq!(**app.world.get::<TestUsize>(a).unwrap(), 6);
+    assert_eq!(**app.world.get::<TestUsize>(b).unwrap(), 8);
+    assert_eq!(take_log(&mut app), ["damage 4", "damage 2"]);
+}
+
+#[test]
+fn merge_per_key() {
+    let mut app = log_app(CommandDedupPlugin);
+    app.insert_resource(TestUsize(0));
+    app.add_systems(Update, |mut commands: Commands| {
+        commands.add_bonus(1, 1);
+        commands.add_bonus(10, 1);
+        commands.add_bonus(1, 2);
+    });
+
+    app.update();
+    assert_eq!(value(&app), 13);
+    assert_eq!(take_log(&mut app), ["bonus 1 3", "bonus 10 1"]);
+}
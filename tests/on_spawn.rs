@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use bevy_commandify::*;
+
+mod common;
+use common::TestUsize;
+
+#[entity_command]
+fn add(world: &mut World, entity: Entity, n: usize) {
+    **world.get_mut::<TestUsize>(entity).unwrap() += n;
+}
+
+#[entity_command]
+fn double(world: &mut World, entity: Entity) {
+    **world.get_mut::<TestUsize>(entity).unwrap() *= 2;
+}
+
+fn pending_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(PendingCommandsPlugin)
+        .register_on_spawn::<AddEntityCommand>();
+    app
+}
+
+#[test]
+fn pending_entity_commands() {
+    let mut app = pending_app();
+    let entity = app
+        .world
+        .spawn((
+            TestUsize(1),
+            PendingEntityCommands::new()
+                .with(AddEntityCommand { n: 2 })
+                .with(DoubleEntityCommand),
+        ))
+        .id();
+
+    app.update();
+    // applied in the order they were added
+    assert_eq!(**app.world.get::<TestUsize>(entity).unwrap(), 6);
+    assert!(app.world.get::<PendingEntityCommands>(entity).is_none());
+
+    // only applied once
+    app.update();
+    assert_eq!(**app.world.get::<TestUsize>(entity).unwrap(), 6);
+}
+
+#[test]
+fn on_spawn() {
+    let mut app = pending_app();
+    let entity = app
+        .world
+        .spawn((TestUsize(1), OnSpawn(AddEntityCommand { n: 4 })))
+        .id();
+
+    app.update();
+    assert_eq!(**app.world.get::<TestUsize>(entity).unwrap(), 5);
+    assert!(app.world.get::<OnSpawn<AddEntityCommand>>(entity).is_none());
+
+    app.update();
+    assert_eq!(**app.world.get::<TestUsize>(entity).unwrap(), 5);
+}
+
+#[test]
+fn spawned_by_commands() {
+    let mut app = pending_app();
+    app.add_systems(Update, |mut commands: Commands| {
+        commands.spawn((TestUsize(0), OnSpawn(AddEntityCommand { n: 1 })));
+    });
+
+    // entities spawned during `Update` are seen in the same frame
+    app.update();
+    let values = app
+        .world
+        .query::<&TestUsize>()
+        .iter(&app.world)
+        .map(|value| **value)
+        .collect::<Vec<_>>();
+    assert_eq!(values, vec![1]);
+}
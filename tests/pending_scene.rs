@@ -0,0 +1,72 @@
+#![cfg(feature = "reflect")]
+
+use bevy::ecs::entity::EntityHashMap;
+use bevy::prelude::*;
+use bevy::scene::ron;
+use bevy::scene::serde::SceneDeserializer;
+use bevy_commandify::*;
+use serde::de::DeserializeSeed;
+
+mod common;
+use common::TestUsize;
+
+#[entity_command(serde)]
+fn grow(world: &mut World, entity: Entity, n: usize) {
+    world.entity_mut(entity).insert(TestUsize(n));
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins(PendingCommandsPlugin)
+        .register_command::<GrowEntityCommand>();
+    app
+}
+
+/// Pending requests should survive being saved and loaded as part of a scene
+#[test]
+fn scene_round_trip() {
+    let mut app = app();
+    let registry = app.world.resource::<AppTypeRegistry>().clone();
+
+    let mut source = World::new();
+    source.insert_resource(registry.clone());
+    source.spawn(PendingEntityRequests::new().with(GrowEntityCommand { n: 3 }));
+    let saved = DynamicScene::from_world(&source)
+        .serialize_ron(&registry)
+        .unwrap();
+    assert!(saved.contains("grow"), "{saved}");
+
+    let scene = {
+        let mut deserializer = ron::de::Deserializer::from_str(&saved).unwrap();
+        SceneDeserializer {
+            type_registry: &registry.read(),
+        }
+        .deserialize(&mut deserializer)
+        .unwrap()
+    };
+    let mut entity_map = EntityHashMap::default();
+    scene
+        .write_to_world(&mut app.world, &mut entity_map)
+        .unwrap();
+    let entity = *entity_map.values().next().unwrap();
+
+    app.update();
+    assert_eq!(**app.world.get::<TestUsize>(entity).unwrap(), 3);
+    assert!(app.world.get::<PendingEntityRequests>(entity).is_none());
+}
+
+/// Unknown commands are skipped, without stopping the rest from being applied
+#[test]
+fn unknown_command() {
+    let mut app = app();
+    let mut pending = PendingEntityRequests::new();
+    pending.requests.push(PendingRequest {
+        command: "shrink".into(),
+        ..Default::default()
+    });
+    pending.push(&GrowEntityCommand { n: 1 }).unwrap();
+    let entity = app.world.spawn(pending).id();
+
+    app.update();
+    assert_eq!(**app.world.get::<TestUsize>(entity).unwrap(), 1);
+}
@@ -0,0 +1,143 @@
+use bevy::prelude::*;
+use bevy_commandify::*;
+
+mod common;
+use common::{log_app, take_log, Log, TestUsize};
+
+#[entity_command(priority = 10)]
+fn damage(world: &mut World, entity: Entity, n: usize) {
+    **world.get_mut::<TestUsize>(entity).unwrap() -= n;
+    world.resource_mut::<Log>().0.push(format!("damage {n}"));
+}
+
+#[entity_command(after = damage)]
+fn kill(world: &mut World, entity: Entity) {
+    world.despawn(entity);
+    world.resource_mut::<Log>().0.push("kill".into());
+}
+
+#[command(priority = -1)]
+fn report(world: &mut World) {
+    world.resource_mut::<Log>().0.push("report".into());
+}
+
+#[command(after = (report, damage), priority = 100)]
+fn cleanup(world: &mut World) {
+    world.resource_mut::<Log>().0.push("cleanup".into());
+}
+
+#[command(after = pong, priority = 1)]
+fn ping(world: &mut World) {
+    world.resource_mut::<Log>().0.push("ping".into());
+}
+
+#[command(after = ping)]
+fn pong(world: &mut World) {
+    world.resource_mut::<Log>().0.push("pong".into());
+}
+
+#[command(priority = 1, merge = sum_bonus)]
+fn bonus(world: &mut World, n: usize) {
+    world.resource_mut::<Log>().0.push(format!("bonus {n}"));
+}
+
+fn sum_bonus(last: &mut BonusCommand, next: BonusCommand) {
+    last.n += next.n;
+}
+
+mod archive {
+    use super::*;
+
+    /// Commands in other modules are named by their path
+    #[command(after = super::report)]
+    pub fn archive(world: &mut World) {
+        world.resource_mut::<Log>().0.push("archive".into());
+    }
+}
+use archive::*;
+
+#[test]
+fn priority_and_after() {
+    let mut app = log_app(CommandPriorityPlugin);
+    let entity = app.world.spawn(TestUsize(10)).id();
+    app.add_systems(Update, move |mut commands: Commands| {
+        commands.cleanup();
+        commands.report();
+        commands.entity(entity).kill();
+    });
+    app.add_systems(Update, move |mut commands: Commands| {
+        commands.entity(entity).damage(1);
+        commands.entity(entity).damage(2);
+    });
+
+    app.update();
+    assert_eq!(
+        take_log(&mut app),
+        ["damage 1", "damage 2", "kill", "report", "cleanup"]
+    );
+    assert!(app.world.get_entity(entity).is_none());
+    assert!(app.world.resource::<CommandPriorityQueue>().is_empty());
+}
+
+#[test]
+fn after_path() {
+    let mut app = log_app(CommandPriorityPlugin);
+    app.add_systems(Update, |mut commands: Commands| {
+        commands.archive();
+        commands.report();
+    });
+
+    app.update();
+    assert_eq!(take_log(&mut app), ["report", "archive"]);
+}
+
+/// Commands waiting on each other are applied by priority alone
+#[test]
+fn cyclic() {
+    let mut app = log_app(CommandPriorityPlugin);
+    app.add_systems(Update, |mut commands: Commands| {
+        commands.pong();
+        commands.ping();
+    });
+
+    app.update();
+    assert_eq!(take_log(&mut app), ["ping", "pong"]);
+}
+
+/// Commands with both `priority` and `merge` are buffered when issued, then merged and applied at the end of the frame
+#[test]
+fn merged() {
+    let mut app = log_app((CommandPriorityPlugin, CommandDedupPlugin));
+    app.add_systems(Update, |mut commands: Commands| {
+        commands.bonus(1);
+        commands.bonus(2);
+    });
+
+    app.update();
+    assert_eq!(take_log(&mut app), ["bonus 3"]);
+    assert!(app.world.resource::<CommandPriorityQueue>().is_empty());
+}
+
+#[test]
+fn despawned() {
+    let mut app = log_app(CommandPriorityPlugin);
+    let entity = app.world.spawn(TestUsize(10)).id();
+    app.add_systems(Update, move |mut commands: Commands| {
+        commands.entity(entity).damage(1);
+        // not prioritized, so applied first
+        commands.entity(entity).despawn();
+    });
+
+    app.update();
+    assert!(take_log(&mut app).is_empty());
+}
+
+#[test]
+fn without_plugin() {
+    let mut world = World::new();
+    world.init_resource::<Log>();
+
+    world.cleanup();
+    world.report();
+    assert_eq!(world.resource::<Log>().0, ["cleanup", "report"]);
+}
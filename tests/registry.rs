@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use bevy_commandify::*;
+use std::marker::PhantomData;
+
+mod common;
+use common::TestUsize;
+
+/// Subtracts `n` from `TestUsize`
+///
+/// Saturates at zero
+#[command]
+fn foo(world: &mut World, n: usize) {
+    let mut m = world.resource_mut::<TestUsize>();
+    **m = m.saturating_sub(n);
+}
+
+#[entity_command(name = "bus")]
+fn bar(In((_entity, _items)): In<(Entity, Vec<usize>)>) {}
+
+#[command]
+fn generic_foo<T: Send + Sync + 'static>(_world: &mut World, _phantom: PhantomData<T>) {}
+
+/// Metadata should be generated for every command struct
+#[test]
+fn command_info() {
+    let info = FooCommand::INFO;
+    assert_eq!(info.name, "foo");
+    assert_eq!(info.struct_name, "FooCommand");
+    assert_eq!(info.kind, CommandKind::Command);
+    assert_eq!(
+        info.params,
+        &[ParamInfo {
+            name: "n",
+            ty: "usize"
+        }]
+    );
+    assert_eq!(
+        info.docs,
+        "Subtracts `n` from `TestUsize`\n\nSaturates at zero"
+    );
+
+    let info = BusEntityCommand::INFO;
+    assert_eq!(info.name, "bus");
+    assert_eq!(info.struct_name, "BusEntityCommand");
+    assert_eq!(info.kind, CommandKind::EntityCommand);
+    assert_eq!(
+        info.params,
+        &[ParamInfo {
+            name: "_items",
+            ty: "Vec<usize>"
+        }]
+    );
+    assert_eq!(info.docs, "");
+}
+
+/// Registered commands should be discoverable by name or type
+#[test]
+fn register_commands() {
+    let mut app = App::new();
+    app.register_command::<FooCommand>()
+        .register_command::<BusEntityCommand>()
+        .register_command::<GenericFooCommand<()>>();
+
+    let registry = app.world.resource::<CommandRegistry>();
+    assert_eq!(registry.len(), 3);
+    assert_eq!(
+        registry.get("bus").unwrap().type_id(),
+        std::any::TypeId::of::<BusEntityCommand>()
+    );
+    assert_eq!(
+        registry.get_by_type::<FooCommand>().unwrap().info().name,
+        "foo"
+    );
+    assert!(registry.get("bar").is_none());
+
+    let names = registry.iter().map(|r| r.info().name).collect::<Vec<_>>();
+    assert_eq!(names, ["foo", "bus", "generic_foo"]);
+}
+
+/// Registering a command under an existing name replaces it
+#[test]
+fn register_replaces() {
+    let mut world = World::new();
+    world
+        .register_command::<GenericFooCommand<u8>>()
+        .register_command::<GenericFooCommand<u16>>();
+
+    let registry = world.resource::<CommandRegistry>();
+    assert_eq!(registry.len(), 1);
+    assert!(registry.get_by_type::<GenericFooCommand<u8>>().is_none());
+    assert!(registry.get_by_type::<GenericFooCommand<u16>>().is_some());
+}
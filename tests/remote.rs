@@ -0,0 +1,205 @@
+#![cfg(feature = "remote")]
+
+use bevy::prelude::*;
+use bevy_commandify::remote::{RemoteRequest, RemoteResponse};
+use bevy_commandify::*;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+mod common;
+use common::TestUsize;
+
+#[command(serde)]
+fn sub(world: &mut World, n: usize) {
+    **world.resource_mut::<TestUsize>() -= n;
+}
+
+#[entity_command(serde)]
+fn heal(world: &mut World, entity: Entity, amount: usize) {
+    **world.get_mut::<TestUsize>(entity).unwrap() += amount;
+}
+
+#[command(serde)]
+fn noop(_world: &mut World) {}
+
+#[command]
+fn local_only(_world: &mut World) {}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins(RemotePlugin::new("127.0.0.1:0"))
+        .register_command::<SubCommand>()
+        .register_command::<HealEntityCommand>()
+        .register_command::<NoopCommand>()
+        .register_command::<LocalOnlyCommand>()
+        .insert_resource(TestUsize(10));
+    app
+}
+
+/// Sends each request on its own line, updating the app until every response arrives
+fn send(app: &mut App, requests: &[String]) -> Vec<RemoteResponse> {
+    let addr = app.world.resource::<RemoteServer>().local_addr();
+    let requests = requests.to_vec();
+    let client = std::thread::spawn(move || {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut responses = Vec::new();
+        for request in requests {
+            writeln!(stream, "{request}").unwrap();
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            responses.push(serde_json::from_str(&line).unwrap());
+        }
+        responses
+    });
+
+    let start = Instant::now();
+    while !client.is_finished() {
+        assert!(start.elapsed() < Duration::from_secs(10), "timed out");
+        app.update();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    client.join().unwrap()
+}
+
+/// Requests over loopback should be applied and acknowledged
+#[test]
+fn remote_commands() {
+    let mut app = app();
+    let entity = app.world.spawn(TestUsize(0)).id();
+
+    let responses = send(
+        &mut app,
+        &[
+            r#"{ "id": 1, "command": "sub", "args": { "n": 4 } }"#.into(),
+            format!(
+                r#"{{ "id": "a", "command": "heal", "entity": "{entity:?}", "args": {{ "amount": 2 }} }}"#
+            ),
+            format!(
+                r#"{{ "command": "heal", "entity": {}, "args": {{ "amount": 3 }} }}"#,
+                entity.to_bits()
+            ),
+            r#"{ "id": 2, "command": "noop" }"#.into(),
+        ],
+    );
+
+    assert_eq!(
+        responses
+            .iter()
+            .map(|r| (r.id.clone(), r.ok))
+            .collect::<Vec<_>>(),
+        [
+            (1.into(), true),
+            ("a".into(), true),
+            (serde_json::Value::Null, true),
+            (2.into(), true)
+        ]
+    );
+    assert_eq!(**app.world.resource::<TestUsize>(), 6);
+    assert_eq!(**app.world.get::<TestUsize>(entity).unwrap(), 5);
+}
+
+/// Bad requests should be answered with an error, and not applied
+#[test]
+fn remote_errors() {
+    let mut app = app();
+
+    let responses = send(
+        &mut app,
+        &[
+            "not json".into(),
+            r#"{ "id": 1, "command": "nope" }"#.into(),
+            r#"{ "id": 2, "command": "local_only" }"#.into(),
+            r#"{ "id": 3, "command": "sub", "args": { "n": "four" } }"#.into(),
+            r#"{ "id": 4, "command": "heal", "args": { "amount": 2 } }"#.into(),
+            r#"{ "id": 5, "command": "sub", "entity": 0, "args": { "n": 1 } }"#.into(),
+        ],
+    );
+
+    for response in &responses {
+        assert!(!response.ok);
+        assert!(response.error.is_some());
+    }
+    assert_eq!(
+        responses[1].error.as_deref(),
+        Some("unknown command `nope`")
+    );
+    assert_eq!(
+        responses[4].error.as_deref(),
+        Some("entity command `heal` requires an entity")
+    );
+    assert_eq!(**app.world.resource::<TestUsize>(), 10);
+}
+
+/// Commands may be run from a `CommandRequest` directly
+#[test]
+fn run_json_command() {
+    let mut world = World::new();
+    world.register_command::<SubCommand>();
+    world.insert_resource(TestUsize(10));
+
+    let request = CommandRequest::new(&SubCommand { n: 3 }, None).unwrap();
+    assert_eq!(
+        serde_json::to_string(&request).unwrap(),
+        r#"{"command":"sub","args":{"n":3},"command_id":4957806865138421,"version":1}"#
+    );
+    world.run_json_command(request).unwrap();
+
+    assert_eq!(**world.resource::<TestUsize>(), 7);
+}
+
+/// A request's stable command id should survive alongside its RPC id
+#[test]
+fn remote_command_id() {
+    let mut app = app();
+
+    let request = RemoteRequest {
+        id: 7.into(),
+        request: CommandRequest {
+            // the id takes priority over an outdated name
+            command: "old_sub".into(),
+            ..CommandRequest::new(&SubCommand { n: 4 }, None).unwrap()
+        },
+    };
+    let line = serde_json::to_string(&request).unwrap();
+    assert_eq!(
+        serde_json::from_str::<RemoteRequest>(&line).unwrap(),
+        request
+    );
+
+    let responses = send(&mut app, &[line]);
+    assert_eq!(responses[0].id, serde_json::Value::from(7));
+    assert!(responses[0].ok, "{:?}", responses[0].error);
+    assert_eq!(**app.world.resource::<TestUsize>(), 6);
+}
+
+/// Connections beyond the maximum should be closed, rather than served
+#[test]
+fn max_connections() {
+    let mut app = App::new();
+    app.add_plugins(RemotePlugin::new("127.0.0.1:0").with_max_connections(1));
+    let addr = app.world.resource::<RemoteServer>().local_addr();
+
+    let _first = TcpStream::connect(addr).unwrap();
+    let mut second = TcpStream::connect(addr).unwrap();
+    second
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    assert_eq!(second.read(&mut [0; 1]).unwrap(), 0);
+}
+
+/// Dropping the app should close open connections and release the port
+#[test]
+fn shutdown() {
+    let app = app();
+    let addr = app.world.resource::<RemoteServer>().local_addr();
+    let mut client = TcpStream::connect(addr).unwrap();
+    client
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+
+    drop(app);
+    assert_eq!(client.read(&mut [0; 1]).unwrap(), 0);
+    TcpListener::bind(addr).unwrap();
+}
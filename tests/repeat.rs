@@ -0,0 +1,124 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_commandify::*;
+use std::time::Duration;
+
+mod common;
+use common::{value, TestUsize};
+
+#[command(clone)]
+fn plus(world: &mut World, n: usize) {
+    **world.resource_mut::<TestUsize>() += n;
+}
+
+#[entity_command(clone)]
+fn regen(world: &mut World, entity: Entity, hp: usize) {
+    **world.get_mut::<TestUsize>(entity).unwrap() += hp;
+}
+
+/// Each frame advances `Time` by 100ms
+fn repeat_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((bevy::time::TimePlugin, CommandSchedulerPlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )))
+        .insert_resource(TestUsize(0));
+    // the first frame's delta is always zero
+    app.update();
+    app
+}
+
+#[test]
+fn every() {
+    let mut app = repeat_app();
+    let token = app.world.repeat(
+        Repeat::every(Duration::from_millis(200)),
+        PlusCommand { n: 1 },
+    );
+
+    for _ in 0..6 {
+        app.update();
+    }
+    assert_eq!(value(&app), 3);
+
+    token.cancel();
+    app.update();
+    app.update();
+    assert_eq!(value(&app), 3);
+    assert!(token.is_cancelled());
+    assert!(app.world.resource::<CommandScheduler>().is_empty());
+}
+
+#[test]
+fn times() {
+    let mut app = repeat_app();
+    let token = app.world.repeat(
+        Repeat::every(Duration::from_millis(100)).times(3),
+        PlusCommand { n: 2 },
+    );
+
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(value(&app), 6);
+    assert!(token.is_applied());
+}
+
+#[test]
+fn catch_up() {
+    let mut app = repeat_app();
+    app.world.repeat(
+        Repeat::every(Duration::from_millis(25)).times(10),
+        PlusCommand { n: 1 },
+    );
+
+    // due four times each frame
+    app.update();
+    assert_eq!(value(&app), 4);
+    app.update();
+    app.update();
+    assert_eq!(value(&app), 10);
+}
+
+#[test]
+fn run_while() {
+    let mut app = repeat_app();
+    app.add_systems(Update, |mut commands: Commands, mut issued: Local<bool>| {
+        if !*issued {
+            commands.repeat(
+                Repeat::every(Duration::ZERO)
+                    .run_while(|world| **world.resource::<TestUsize>() < 5),
+                PlusCommand { n: 1 },
+            );
+            *issued = true;
+        }
+    });
+
+    // applied once per frame, starting the frame after being issued
+    app.update();
+    assert_eq!(value(&app), 0);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(value(&app), 5);
+}
+
+#[test]
+fn entity_despawned() {
+    let mut app = repeat_app();
+    let entity = app.world.spawn(TestUsize(0)).id();
+    let token = app.world.entity_mut(entity).repeat(
+        Repeat::every(Duration::from_millis(100)),
+        RegenEntityCommand { hp: 1 },
+    );
+
+    app.update();
+    app.update();
+    assert_eq!(**app.world.get::<TestUsize>(entity).unwrap(), 2);
+
+    app.world.despawn(entity);
+    app.update();
+    assert!(token.is_cancelled());
+    assert!(app.world.resource::<CommandScheduler>().is_empty());
+}
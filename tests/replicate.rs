@@ -0,0 +1,159 @@
+#![cfg(feature = "serde")]
+
+use bevy::prelude::*;
+use bevy_commandify::*;
+
+mod common;
+use common::TestUsize;
+
+#[derive(Component, Debug, PartialEq)]
+struct Target(Entity);
+
+#[command(serde, replicate)]
+fn add_total(world: &mut World, n: usize) {
+    **world.resource_mut::<TestUsize>() += n;
+}
+
+#[entity_command(serde, replicate)]
+fn set_count(entity: Entity, world: &mut World, n: usize, target: Entity) {
+    world
+        .entity_mut(entity)
+        .insert((TestUsize(n), Target(target)));
+}
+
+#[command(serde)]
+fn not_replicated(world: &mut World) {
+    **world.resource_mut::<TestUsize>() += 1000;
+}
+
+fn apps() -> (App, App) {
+    let (client_transport, server_transport) = LoopbackTransport::pair();
+
+    let mut client = App::new();
+    client
+        .add_plugins(ReplicationPlugin)
+        .insert_resource(TestUsize(0))
+        .insert_resource(ReplicationClient::new(client_transport));
+
+    let mut server = App::new();
+    let mut replication = ReplicationServer::default();
+    replication.add_client(server_transport);
+    server
+        .add_plugins(ReplicationPlugin)
+        .insert_resource(TestUsize(0))
+        .insert_resource(replication)
+        .register_command::<AddTotalCommand>()
+        .register_command::<SetCountEntityCommand>()
+        .register_command::<NotReplicatedCommand>();
+
+    (client, server)
+}
+
+/// Replicated commands should be applied on the server rather than the client
+#[test]
+fn replicate_commands() {
+    let (mut client, mut server) = apps();
+
+    client.world.add_total(5);
+    client.world.not_replicated();
+    assert_eq!(
+        client.world.resource::<ReplicationClient>().outbox().len(),
+        1
+    );
+    assert_eq!(**client.world.resource::<TestUsize>(), 1000);
+
+    client.update();
+    server.update();
+    assert_eq!(**server.world.resource::<TestUsize>(), 5);
+}
+
+/// Client entities should be mapped to the same server entity each time they are sent
+#[test]
+fn map_entities() {
+    let (mut client, mut server) = apps();
+
+    let existing = server.world.spawn_empty().id();
+    let a = client.world.spawn_empty().id();
+    let b = client.world.spawn_empty().id();
+    let c = client.world.spawn_empty().id();
+    server
+        .world
+        .resource_mut::<ReplicationServer>()
+        .map_entity(ClientId(0), c, existing)
+        .unwrap();
+
+    client.world.entity_mut(a).set_count(1, b);
+    client.world.entity_mut(b).set_count(2, c);
+    client.update();
+    server.update();
+
+    let replication = server.world.resource::<ReplicationServer>();
+    let server_a = replication.server_entity(ClientId(0), a).unwrap();
+    let server_b = replication.server_entity(ClientId(0), b).unwrap();
+    assert_ne!(server_a, server_b);
+    assert_eq!(replication.server_entity(ClientId(0), c), Some(existing));
+
+    assert_eq!(**server.world.get::<TestUsize>(server_a).unwrap(), 1);
+    assert_eq!(
+        server.world.get::<Target>(server_a),
+        Some(&Target(server_b))
+    );
+    assert_eq!(**server.world.get::<TestUsize>(server_b).unwrap(), 2);
+    assert_eq!(
+        server.world.get::<Target>(server_b),
+        Some(&Target(existing))
+    );
+    // nothing is applied on the client
+    assert!(client.world.get::<TestUsize>(a).is_none());
+}
+
+/// The server should only accept commands marked for replication
+#[test]
+fn reject_unreplicated() {
+    let (_, mut server) = apps();
+
+    let request = CommandRequest {
+        command: "not_replicated".into(),
+        entity: None,
+        args: Default::default(),
+        id: None,
+        version: None,
+    };
+    let message = serde_json::to_vec(&request).unwrap();
+    let result =
+        bevy_commandify::replicate::apply_replicated(&mut server.world, ClientId(0), &message);
+    assert!(matches!(result, Err(ReplicationError::NotReplicated(_))));
+    assert_eq!(**server.world.resource::<TestUsize>(), 0);
+}
+
+/// Unknown clients and missing servers should be reported rather than panicking
+#[test]
+fn unknown_client() {
+    let (_, mut server) = apps();
+
+    let entity = server.world.spawn_empty().id();
+    let result =
+        server
+            .world
+            .resource_mut::<ReplicationServer>()
+            .map_entity(ClientId(1), entity, entity);
+    assert!(matches!(
+        result,
+        Err(ReplicationError::UnknownClient(ClientId(1)))
+    ));
+
+    let request = CommandRequest::new(&AddTotalCommand { n: 1 }, None).unwrap();
+    let message = serde_json::to_vec(&request).unwrap();
+    let result =
+        bevy_commandify::replicate::apply_replicated(&mut server.world, ClientId(1), &message);
+    assert!(matches!(
+        result,
+        Err(ReplicationError::UnknownClient(ClientId(1)))
+    ));
+
+    server.world.remove_resource::<ReplicationServer>();
+    let result =
+        bevy_commandify::replicate::apply_replicated(&mut server.world, ClientId(0), &message);
+    assert!(matches!(result, Err(ReplicationError::NoServer)));
+    assert_eq!(**server.world.resource::<TestUsize>(), 0);
+}
@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+use bevy_commandify::*;
+
+mod common;
+use common::TestUsize;
+
+#[derive(Resource)]
+struct Level;
+
+#[derive(Resource)]
+struct Score(usize);
+
+#[derive(States, Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum GameState {
+    #[default]
+    Menu,
+    Playing,
+}
+
+#[command(run_if = resource_exists::<Level>)]
+fn plus(world: &mut World, n: usize) {
+    **world.resource_mut::<TestUsize>() += n;
+}
+
+#[command(run_if = in_state(GameState::Playing), on_skip = log)]
+fn multiply(In(n): In<usize>, mut value: ResMut<TestUsize>) {
+    **value *= n;
+}
+
+#[entity_command(run_if = resource_exists::<Level>)]
+fn increase(world: &mut World, entity: Entity, n: usize) {
+    **world.get_mut::<TestUsize>(entity).unwrap() += n;
+}
+
+/// Conditions are kept between applications, so this is only applied once per change
+#[command(run_if = resource_changed::<Score>)]
+fn announce(world: &mut World) {
+    **world.resource_mut::<TestUsize>() += 1;
+}
+
+#[command(run_if = resource_exists::<Level>, on_skip = panic)]
+fn required() {}
+
+fn value(world: &World) -> usize {
+    **world.resource::<TestUsize>()
+}
+
+#[test]
+fn skipped_without_resource() {
+    let mut world = World::new();
+    world.insert_resource(TestUsize(0));
+    let entity = world.spawn(TestUsize(0)).id();
+
+    world.plus(1);
+    world.entity_mut(entity).increase(1);
+    assert_eq!(value(&world), 0);
+    assert_eq!(**world.get::<TestUsize>(entity).unwrap(), 0);
+
+    world.insert_resource(Level);
+    world.plus(1);
+    world.entity_mut(entity).increase(1);
+    assert_eq!(value(&world), 1);
+    assert_eq!(**world.get::<TestUsize>(entity).unwrap(), 1);
+}
+
+#[test]
+fn skipped_outside_state() {
+    let mut app = App::new();
+    app.insert_resource(TestUsize(2)).init_state::<GameState>();
+
+    app.world.multiply(3);
+    assert_eq!(value(&app.world), 2);
+
+    app.world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+    app.update();
+    app.world.multiply(3);
+    assert_eq!(value(&app.world), 6);
+}
+
+#[test]
+fn changed_since_applied() {
+    let mut world = World::new();
+    world.insert_resource(TestUsize(0));
+    world.insert_resource(Score(0));
+
+    world.announce();
+    world.announce();
+    assert_eq!(value(&world), 1);
+
+    world.resource_mut::<Score>().0 += 1;
+    world.announce();
+    world.announce();
+    assert_eq!(value(&world), 2);
+}
+
+#[test]
+#[should_panic(expected = "command `required` failed")]
+fn on_skip_panic() {
+    let mut world = World::new();
+    world.required();
+}
+
+#[test]
+fn not_recorded() {
+    let mut world = World::new();
+    world.insert_resource(TestUsize(0));
+    world.insert_resource(UndoHistory::default());
+
+    #[command(run_if = resource_exists::<Level>, undo = restore)]
+    fn set(world: &mut World, n: usize) {
+        **world.resource_mut::<TestUsize>() = n;
+    }
+    fn restore(_command: &SetCommand, world: &mut World) -> impl bevy::ecs::system::Command {
+        let old = **world.resource::<TestUsize>();
+        move |world: &mut World| **world.resource_mut::<TestUsize>() = old
+    }
+
+    world.set(5);
+    assert!(!world.resource::<UndoHistory>().can_undo());
+    world.insert_resource(Level);
+    world.set(5);
+    assert!(world.resource::<UndoHistory>().can_undo());
+}
@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+use bevy_commandify::*;
+
+mod common;
+use common::{value, TestUsize};
+
+#[command(clone)]
+fn plus(world: &mut World, n: usize) {
+    **world.resource_mut::<TestUsize>() += n;
+}
+
+#[derive(States, Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum GameState {
+    #[default]
+    Menu,
+    Playing,
+}
+
+#[derive(Resource)]
+struct Enabled(bool);
+
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+struct Bonus;
+
+#[test]
+fn add_command() {
+    let mut app = App::new();
+    app.insert_resource(TestUsize(0))
+        .add_command(Startup, PlusCommand { n: 10 })
+        .add_command(Update, PlusCommand { n: 1 });
+
+    app.update();
+    assert_eq!(value(&app), 11);
+    app.update();
+    assert_eq!(value(&app), 12);
+}
+
+#[test]
+fn run_if() {
+    let mut app = App::new();
+    app.insert_resource(TestUsize(0))
+        .insert_resource(Enabled(false))
+        .add_systems(
+            Update,
+            run_command(PlusCommand { n: 1 }).run_if(|enabled: Res<Enabled>| enabled.0),
+        );
+
+    app.update();
+    assert_eq!(value(&app), 0);
+
+    app.world.resource_mut::<Enabled>().0 = true;
+    app.update();
+    assert_eq!(value(&app), 1);
+}
+
+#[test]
+fn add_command_run_if() {
+    let mut app = App::new();
+    app.insert_resource(TestUsize(0))
+        .insert_resource(Enabled(false))
+        .add_command(
+            Update,
+            PlusCommand { n: 1 }.run_if(|enabled: Res<Enabled>| enabled.0),
+        )
+        .add_command(
+            Update,
+            PlusCommand { n: 10 }
+                .after(Bonus)
+                .run_if(|enabled: Res<Enabled>| enabled.0),
+        )
+        .add_command(Update, PlusCommand { n: 100 }.in_set(Bonus));
+
+    app.update();
+    assert_eq!(value(&app), 100);
+
+    app.world.resource_mut::<Enabled>().0 = true;
+    app.update();
+    assert_eq!(value(&app), 211);
+}
+
+#[test]
+fn states() {
+    let mut app = App::new();
+    app.insert_resource(TestUsize(0))
+        .init_state::<GameState>()
+        .add_command_on_enter(GameState::Playing, PlusCommand { n: 1 })
+        .add_command_on_exit(GameState::Playing, PlusCommand { n: 10 });
+
+    app.update();
+    assert_eq!(value(&app), 0);
+
+    app.world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+    app.update();
+    app.update();
+    assert_eq!(value(&app), 1);
+
+    app.world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Menu);
+    app.update();
+    assert_eq!(value(&app), 11);
+}
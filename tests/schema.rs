@@ -0,0 +1,132 @@
+#![cfg(feature = "serde")]
+
+use bevy::prelude::*;
+use bevy_commandify::*;
+use serde_json::json;
+use std::marker::PhantomData;
+
+/// Spawns an enemy
+#[command(serde, schema)]
+fn spawn_enemy(
+    _world: &mut World,
+    /// Where to spawn the enemy
+    position: (f32, f32),
+    #[serde(rename = "kind")] name: String,
+    #[serde(default)] level: u32,
+    #[serde(default = "default_tags")] tags: Vec<String>,
+    boss: Option<bool>,
+) {
+    let _ = (position, name, level, tags, boss);
+}
+
+fn default_tags() -> Vec<String> {
+    vec!["hostile".into()]
+}
+
+#[entity_command(serde, schema)]
+fn heal(_world: &mut World, _entity: Entity, #[serde(rename = "amount")] _amount: usize) {}
+
+#[command(serde, schema)]
+fn generic<T: Send + Sync + 'static>(_world: &mut World, _phantom: PhantomData<T>) {}
+
+#[command(serde)]
+fn no_schema(_world: &mut World) {}
+
+/// Not serializable, nor described by a schema
+#[derive(Default)]
+struct Cache;
+
+#[command(serde, schema)]
+fn skipped(
+    _world: &mut World,
+    #[serde(skip)] _cache: Cache,
+    #[serde(skip_deserializing)] _seen: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")] note: Option<String>,
+    #[serde(skip_serializing)] label: String,
+    count: u32,
+) {
+    let _ = (note, label, count);
+}
+
+/// The schema should describe each field, its docs and defaults
+#[test]
+fn command_schema() {
+    assert_eq!(
+        SpawnEnemyCommand::json_schema(),
+        json!({
+            "title": "SpawnEnemyCommand",
+            "description": "Spawns an enemy",
+            "type": "object",
+            "properties": {
+                "position": {
+                    "type": "array",
+                    "prefixItems": [{ "type": "number" }, { "type": "number" }],
+                    "minItems": 2,
+                    "maxItems": 2,
+                    "description": "Where to spawn the enemy",
+                },
+                "kind": { "type": "string" },
+                "level": { "type": "integer", "minimum": 0, "default": 0 },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "default": ["hostile"],
+                },
+                "boss": { "anyOf": [{ "type": "boolean" }, { "type": "null" }] },
+            },
+            "required": ["position", "kind"],
+            "additionalProperties": false,
+        })
+    );
+}
+
+/// Skipped fields should not be described, and fields which may not be serialized should not be required
+#[test]
+fn skipped_schema() {
+    assert_eq!(
+        SkippedCommand::json_schema(),
+        json!({
+            "title": "SkippedCommand",
+            "type": "object",
+            "properties": {
+                "note": { "anyOf": [{ "type": "string" }, { "type": "null" }], "default": null },
+                "label": { "type": "string" },
+                "count": { "type": "integer", "minimum": 0 },
+            },
+            "required": ["count"],
+            "additionalProperties": false,
+        })
+    );
+}
+
+/// The registry schema should validate requests for every command with a schema
+#[test]
+fn registry_schema() {
+    let mut world = World::new();
+    world
+        .register_command::<SpawnEnemyCommand>()
+        .register_command::<HealEntityCommand>()
+        .register_command::<GenericCommand<u8>>()
+        .register_command::<NoSchemaCommand>();
+    let registry = world.resource::<CommandRegistry>();
+
+    let schema = registry.json_schema();
+    let commands = schema["oneOf"].as_array().unwrap();
+    assert_eq!(commands.len(), 3);
+
+    let heal = &commands[1];
+    assert_eq!(heal["properties"]["command"], json!({ "const": "heal" }));
+    assert_eq!(heal["required"], json!(["command", "entity", "args"]));
+    assert_eq!(
+        heal["properties"]["args"]["properties"]["amount"],
+        json!({ "type": "integer", "minimum": 0 })
+    );
+    assert_eq!(commands[2]["required"], json!(["command", "args"]));
+
+    let path = std::env::temp_dir().join("bevy_commandify_schema.json");
+    registry.write_json_schema(&path).unwrap();
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(written, schema);
+}
@@ -0,0 +1,116 @@
+#![cfg(feature = "scripts")]
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_commandify::*;
+use std::time::Duration;
+
+mod common;
+use common::TestUsize;
+
+#[command(serde)]
+fn spawn_counter(world: &mut World, n: usize) {
+    world.spawn(TestUsize(n));
+}
+
+#[command(serde)]
+fn add_total(world: &mut World, n: usize) {
+    **world.resource_mut::<TestUsize>() += n;
+}
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin {
+            file_path: "tests/assets".into(),
+            ..Default::default()
+        },
+        CommandScriptPlugin,
+    ))
+    .insert_resource(TestUsize(0))
+    .register_command::<SpawnCounterCommand>()
+    .register_command::<AddTotalCommand>();
+    app
+}
+
+fn counters(world: &mut World) -> usize {
+    world.query::<&TestUsize>().iter(world).count()
+}
+
+#[test]
+fn parse() {
+    let json = br#"[
+        { "command": "add_total", "args": { "n": 1 } },
+        { "wait": 0.5 },
+        { "wait_frames": 2 }
+    ]"#;
+    let script = CommandScript::from_json(json).unwrap();
+    assert_eq!(script.steps.len(), 3);
+    assert!(
+        matches!(&script.steps[0], ScriptStep::Command(request) if request.command == "add_total")
+    );
+    assert_eq!(script.steps[1], ScriptStep::Wait { wait: 0.5 });
+    assert_eq!(script.steps[2], ScriptStep::WaitFrames { wait_frames: 2 });
+
+    let ron = br#"[(command: "add_total", args: (n: 1)), (wait: 0.5)]"#;
+    let script = CommandScript::from_ron(ron).unwrap();
+    assert_eq!(script.steps.len(), 2);
+
+    let mut world = World::new();
+    world.insert_resource(TestUsize(0));
+    world.register_command::<AddTotalCommand>();
+    script.run(&mut world).unwrap();
+    assert_eq!(**world.resource::<TestUsize>(), 1);
+}
+
+/// Scripts should be loaded through the `AssetServer` and run over several frames
+#[test]
+fn load_and_run() {
+    let mut app = app();
+    let handle = app
+        .world
+        .resource::<AssetServer>()
+        .load::<CommandScript>("intro.commands.ron");
+    let runner = app.world.spawn(ScriptRunner::new(handle)).id();
+
+    // wait for the script to load
+    for _ in 0..1000 {
+        app.update();
+        if counters(&mut app.world) > 0 {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(counters(&mut app.world), 1);
+    assert_eq!(**app.world.resource::<TestUsize>(), 0);
+
+    app.update();
+    assert_eq!(**app.world.resource::<TestUsize>(), 10);
+    assert!(app.world.get::<ScriptRunner>(runner).is_none());
+}
+
+#[test]
+fn wait_seconds() {
+    let mut app = app();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+        100,
+    )));
+    let script = CommandScript::from_json(
+        br#"[{ "wait": 0.25 }, { "command": "add_total", "args": { "n": 1 } }]"#,
+    )
+    .unwrap();
+    let handle = app
+        .world
+        .resource_mut::<Assets<CommandScript>>()
+        .add(script);
+    app.world.spawn(ScriptRunner::new(handle));
+
+    // the first update has no delta
+    for _ in 0..3 {
+        app.update();
+        assert_eq!(**app.world.resource::<TestUsize>(), 0);
+    }
+    app.update();
+    assert_eq!(**app.world.resource::<TestUsize>(), 1);
+}
@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use bevy_commandify::*;
+use std::thread;
+
+mod common;
+use common::TestUsize;
+
+#[command]
+fn plus(world: &mut World, n: usize) {
+    **world.resource_mut::<TestUsize>() += n;
+}
+
+#[command]
+fn multiply(In(n): In<usize>, mut value: ResMut<TestUsize>) -> &mut Self {
+    **value *= n;
+}
+
+#[entity_command]
+fn increase(world: &mut World, entity: Entity, n: usize) {
+    **world.get_mut::<TestUsize>(entity).unwrap() += n;
+}
+
+#[test]
+fn send_from_thread() {
+    let mut app = App::new();
+    app.insert_resource(TestUsize(1))
+        .add_plugins(CommandSenderPlugin);
+    let entity = app.world.spawn(TestUsize(0)).id();
+
+    let mut sender = app.world.resource::<CommandSender>().clone();
+    thread::spawn(move || {
+        sender.plus(2);
+        sender.multiply(3).multiply(2);
+        sender.entity(entity).increase(5);
+    })
+    .join()
+    .unwrap();
+
+    // nothing is applied until the next frame
+    assert_eq!(**app.world.resource::<TestUsize>(), 1);
+    app.update();
+    assert_eq!(**app.world.resource::<TestUsize>(), 18);
+    assert_eq!(**app.world.get::<TestUsize>(entity).unwrap(), 5);
+}
+
+#[test]
+fn request() {
+    let mut app = App::new();
+    app.insert_resource(TestUsize(4))
+        .add_plugins(CommandSenderPlugin);
+
+    let mut sender = app.world.resource::<CommandSender>().clone();
+    let handle = thread::spawn(move || {
+        sender.plus(1);
+        let reply = sender.request(|world| **world.resource::<TestUsize>());
+        reply.recv().unwrap()
+    });
+
+    while !handle.is_finished() {
+        app.update();
+    }
+    assert_eq!(handle.join().unwrap(), 5);
+}
+
+#[test]
+fn dropped_world() {
+    let (mut sender, receiver) = CommandSender::new();
+    drop(receiver);
+
+    sender.plus(1);
+    let reply = sender.request(|_| ());
+    assert!(reply.recv().is_err());
+}
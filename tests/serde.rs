@@ -0,0 +1,157 @@
+#![cfg(feature = "serde")]
+
+use bevy::ecs::entity::{EntityHashMap, MapEntities};
+use bevy::ecs::system::{Command, CommandQueue};
+use bevy::prelude::*;
+use bevy_commandify::*;
+
+mod common;
+use common::TestUsize;
+
+#[command(serde)]
+fn foo(world: &mut World, target: Entity, #[serde(default)] n: usize) {
+    let mut m = world
+        .query::<&mut TestUsize>()
+        .get_mut(world, target)
+        .unwrap();
+    **m -= n;
+}
+
+#[entity_command(serde)]
+fn bar(world: &mut World, entity: Entity, n: usize) {
+    let mut m = world
+        .query::<&mut TestUsize>()
+        .get_mut(world, entity)
+        .unwrap();
+    **m -= n;
+}
+
+#[command(serde, map_entities = map_pair)]
+fn swap(world: &mut World, pair: (Entity, Entity)) {
+    let a = **world.get::<TestUsize>(pair.0).unwrap();
+    let b = **world.get::<TestUsize>(pair.1).unwrap();
+    **world.get_mut::<TestUsize>(pair.0).unwrap() = b;
+    **world.get_mut::<TestUsize>(pair.1).unwrap() = a;
+}
+
+fn map_pair<M: EntityMapper>(command: &mut SwapCommand, entity_mapper: &mut M) {
+    command.pair.0 = entity_mapper.map_entity(command.pair.0);
+    command.pair.1 = entity_mapper.map_entity(command.pair.1);
+}
+
+#[command(serde)]
+fn link(
+    _world: &mut World,
+    parent: Option<Entity>,
+    children: Vec<(Entity, usize)>,
+    pair: [Option<Entity>; 2],
+    n: usize,
+) {
+    let _ = (parent, children, pair, n);
+}
+
+/// Maps entities according to a pre-built lookup table
+struct TableMapper(EntityHashMap<Entity>);
+
+impl EntityMapper for TableMapper {
+    fn map_entity(&mut self, entity: Entity) -> Entity {
+        self.0.get(&entity).copied().unwrap_or(entity)
+    }
+}
+
+/// Commands should survive a serialization round-trip
+#[test]
+fn round_trip() {
+    let mut world = World::new();
+    let entity = world.spawn(TestUsize(10)).id();
+
+    let json = serde_json::to_string(&FooCommand {
+        target: entity,
+        n: 5,
+    })
+    .unwrap();
+    let command: FooCommand = serde_json::from_str(&json).unwrap();
+
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &world);
+
+    commands.add(command);
+    commands
+        .entity(entity)
+        .add(serde_json::from_str::<BarEntityCommand>(r#"{"n":5}"#).unwrap());
+
+    queue.apply(&mut world);
+
+    assert_eq!(**world.get::<TestUsize>(entity).unwrap(), 0);
+}
+
+/// `#[serde(...)]` attributes on fn parameters should be passed through to the struct fields
+#[test]
+fn field_attributes() {
+    let json = format!(r#"{{"target":{}}}"#, Entity::from_raw(0).to_bits());
+    let command: FooCommand = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(command.n, 0);
+}
+
+/// `Entity` fields should be remapped to the entities of the world we load into
+#[test]
+fn entity_mapping() {
+    let mut saved = World::new();
+    let old = saved.spawn_empty().id();
+    let json = serde_json::to_string(&FooCommand { target: old, n: 5 }).unwrap();
+
+    let mut world = World::new();
+    world.spawn_empty();
+    let new = world.spawn(TestUsize(5)).id();
+
+    let mut mapper = TableMapper([(old, new)].into_iter().collect());
+    let mut command: FooCommand = serde_json::from_str(&json).unwrap();
+    command.map_entities(&mut mapper);
+    assert_eq!(command.target, new);
+
+    command.apply(&mut world);
+
+    assert_eq!(**world.get::<TestUsize>(new).unwrap(), 0);
+}
+
+/// Entities within options, vecs, arrays and tuples should be remapped too
+#[test]
+fn nested_entity_mapping() {
+    let old = [100, 101, 102].map(Entity::from_raw);
+    let new = [0, 1, 2].map(Entity::from_raw);
+    let mut mapper = TableMapper(old.into_iter().zip(new).collect());
+
+    let mut command = LinkCommand {
+        parent: Some(old[0]),
+        children: vec![(old[1], 1), (old[2], 2)],
+        pair: [None, Some(old[1])],
+        n: 3,
+    };
+    command.map_entities(&mut mapper);
+    assert_eq!(command.parent, Some(new[0]));
+    assert_eq!(command.children, [(new[1], 1), (new[2], 2)]);
+    assert_eq!(command.pair, [None, Some(new[1])]);
+    assert_eq!(command.n, 3);
+}
+
+/// `map_entities` should replace the default mapping strategy
+#[test]
+fn custom_entity_mapping() {
+    let mut world = World::new();
+    let a = world.spawn(TestUsize(1)).id();
+    let b = world.spawn(TestUsize(2)).id();
+    let old_a = Entity::from_raw(100);
+    let old_b = Entity::from_raw(101);
+
+    let mut command = SwapCommand {
+        pair: (old_a, old_b),
+    };
+    command.map_entities(&mut TableMapper(
+        [(old_a, a), (old_b, b)].into_iter().collect(),
+    ));
+    world.swap(command.pair);
+
+    assert_eq!(**world.get::<TestUsize>(a).unwrap(), 2);
+    assert_eq!(**world.get::<TestUsize>(b).unwrap(), 1);
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use bevy_commandify::*;
+
+mod common;
+use common::{value, TestUsize};
+
+#[command(system_adapter)]
+fn plus(world: &mut World, n: usize) {
+    **world.resource_mut::<TestUsize>() += n;
+}
+
+#[command(system_adapter)]
+fn multiply(In(n): In<usize>, mut value: ResMut<TestUsize>) {
+    **value *= n;
+}
+
+#[command(system_adapter)]
+fn double(mut value: ResMut<TestUsize>) {
+    **value *= 2;
+}
+
+#[entity_command(system_adapter)]
+fn increase(world: &mut World, entity: Entity, n: usize) {
+    **world.get_mut::<TestUsize>(entity).unwrap() += n;
+}
+
+#[entity_command(system_adapter)]
+fn decrease(In((entity, n)): In<(Entity, usize)>, mut query: Query<&mut TestUsize>) {
+    **query.get_mut(entity).unwrap() -= n;
+}
+
+#[test]
+fn system_adapter() {
+    let mut app = App::new();
+    app.insert_resource(TestUsize(0)).add_systems(
+        Update,
+        (plus_system(1), multiply_system(3), double_system()).chain(),
+    );
+
+    app.update();
+    assert_eq!(value(&app), 6);
+    app.update();
+    assert_eq!(value(&app), 42);
+
+    // the same implementation is still available on demand
+    app.world.plus(1);
+    assert_eq!(value(&app), 43);
+}
+
+#[test]
+fn entity_system_adapter() {
+    let mut app = App::new();
+    let entity = app.world.spawn(TestUsize(10)).id();
+    app.add_systems(
+        Update,
+        (increase_system(entity, 5), decrease_system(entity, 2)).chain(),
+    );
+
+    app.update();
+    app.update();
+    assert_eq!(**app.world.get::<TestUsize>(entity).unwrap(), 16);
+}
@@ -0,0 +1,10 @@
+use bevy::prelude::*;
+use bevy_commandify::*;
+
+#[command]
+fn damage(_world: &mut World) {}
+
+#[command(after = damgae)]
+fn kill(_world: &mut World) {}
+
+fn main() {}
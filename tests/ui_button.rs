@@ -0,0 +1,97 @@
+#![cfg(feature = "ui")]
+
+use bevy::prelude::*;
+use bevy_commandify::*;
+
+mod common;
+use common::TestUsize;
+
+#[command(clone)]
+fn add(world: &mut World, n: usize) {
+    **world.resource_mut::<TestUsize>() += n;
+}
+
+#[entity_command(clone)]
+fn increase(world: &mut World, entity: Entity, n: usize) {
+    **world.get_mut::<TestUsize>(entity).unwrap() += n;
+}
+
+fn set_interaction(app: &mut App, button: Entity, interaction: Interaction) {
+    *app.world.get_mut::<Interaction>(button).unwrap() = interaction;
+    app.update();
+}
+
+#[test]
+fn command_button() {
+    let mut app = App::new();
+    app.insert_resource(TestUsize(0))
+        .register_command_button::<AddCommand>();
+    let button = app
+        .world
+        .spawn((Interaction::None, CommandButton::new(AddCommand { n: 2 })))
+        .id();
+
+    app.update();
+    set_interaction(&mut app, button, Interaction::Hovered);
+    assert_eq!(**app.world.resource::<TestUsize>(), 0);
+
+    set_interaction(&mut app, button, Interaction::Pressed);
+    assert_eq!(**app.world.resource::<TestUsize>(), 2);
+
+    // held down
+    app.update();
+    assert_eq!(**app.world.resource::<TestUsize>(), 2);
+
+    set_interaction(&mut app, button, Interaction::Hovered);
+    set_interaction(&mut app, button, Interaction::Pressed);
+    assert_eq!(**app.world.resource::<TestUsize>(), 4);
+}
+
+#[test]
+fn entity_command_button() {
+    let mut app = App::new();
+    app.register_entity_command_button::<IncreaseEntityCommand>();
+    let target = app.world.spawn(TestUsize(0)).id();
+    let own = app
+        .world
+        .spawn((
+            TestUsize(0),
+            Interaction::None,
+            EntityCommandButton::new(IncreaseEntityCommand { n: 1 }),
+        ))
+        .id();
+    let targeted = app
+        .world
+        .spawn((
+            Interaction::None,
+            EntityCommandButton::new(IncreaseEntityCommand { n: 5 }).with_target(target),
+        ))
+        .id();
+
+    set_interaction(&mut app, own, Interaction::Pressed);
+    assert_eq!(**app.world.get::<TestUsize>(own).unwrap(), 1);
+    assert_eq!(**app.world.get::<TestUsize>(target).unwrap(), 0);
+
+    set_interaction(&mut app, targeted, Interaction::Pressed);
+    assert_eq!(**app.world.get::<TestUsize>(own).unwrap(), 1);
+    assert_eq!(**app.world.get::<TestUsize>(target).unwrap(), 5);
+}
+
+/// Pressing a button whose target was despawned should do nothing
+#[test]
+fn despawned_target() {
+    let mut app = App::new();
+    app.register_entity_command_button::<IncreaseEntityCommand>();
+    let target = app.world.spawn(TestUsize(0)).id();
+    let button = app
+        .world
+        .spawn((
+            Interaction::None,
+            EntityCommandButton::new(IncreaseEntityCommand { n: 5 }).with_target(target),
+        ))
+        .id();
+
+    app.world.despawn(target);
+    set_interaction(&mut app, button, Interaction::Pressed);
+    assert!(app.world.get_entity(target).is_none());
+}
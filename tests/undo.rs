@@ -0,0 +1,192 @@
+use bevy::ecs::system::Command;
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+use bevy_commandify::*;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+mod common;
+use common::TestUsize;
+
+/// Set the value
+#[command(undo = restore_value)]
+fn set_value(world: &mut World, n: usize) {
+    **world.resource_mut::<TestUsize>() = n;
+}
+
+fn restore_value(_command: &SetValueCommand, world: &mut World) -> impl Command {
+    let old = **world.resource::<TestUsize>();
+    move |world: &mut World| **world.resource_mut::<TestUsize>() = old
+}
+
+/// Increase an entity
+#[entity_command(undo = decrease)]
+fn increase(In((entity, n)): In<(Entity, usize)>, mut query: Query<&mut TestUsize>) {
+    **query.get_mut(entity).unwrap() += n;
+}
+
+fn decrease(command: &IncreaseEntityCommand, entity: Entity, _world: &mut World) -> impl Command {
+    let n = command.n;
+    move |world: &mut World| **world.get_mut::<TestUsize>(entity).unwrap() -= n
+}
+
+/// Sets the value twice, which should be undone as one step
+#[command(undo = restore_value_twice)]
+fn set_value_twice(world: &mut World, n: usize) {
+    world.set_value(n);
+    world.set_value(n * 2);
+}
+
+fn restore_value_twice(_command: &SetValueTwiceCommand, world: &mut World) -> impl Command {
+    let old = **world.resource::<TestUsize>();
+    move |world: &mut World| **world.resource_mut::<TestUsize>() = old
+}
+
+/// Panics when undone
+#[command(undo = fail_to_restore)]
+fn set_fragile(world: &mut World, n: usize) {
+    **world.resource_mut::<TestUsize>() = n;
+}
+
+fn fail_to_restore(_command: &SetFragileCommand, _world: &mut World) -> impl Command {
+    |_world: &mut World| panic!("cannot restore")
+}
+
+fn value(world: &World) -> usize {
+    **world.resource::<TestUsize>()
+}
+
+#[test]
+fn undo_redo() {
+    let mut world = World::new();
+    world.insert_resource(TestUsize(0));
+    world.init_resource::<UndoHistory>();
+
+    world.set_value(1);
+    world.set_value(2);
+    assert_eq!(value(&world), 2);
+
+    world.undo();
+    assert_eq!(value(&world), 1);
+    world.undo();
+    assert_eq!(value(&world), 0);
+    // nothing left to undo
+    world.undo();
+    assert_eq!(value(&world), 0);
+
+    world.redo();
+    assert_eq!(value(&world), 1);
+    world.redo();
+    assert_eq!(value(&world), 2);
+    assert!(!world.resource::<UndoHistory>().can_redo());
+
+    // redone steps can be undone again
+    world.undo();
+    assert_eq!(value(&world), 1);
+
+    // new commands discard anything undone
+    world.set_value(5);
+    assert!(!world.resource::<UndoHistory>().can_redo());
+    world.undo();
+    world.undo();
+    assert_eq!(value(&world), 0);
+}
+
+#[test]
+fn labels() {
+    let mut world = World::new();
+    world.insert_resource(TestUsize(0));
+    world.init_resource::<UndoHistory>();
+    let entity = world.spawn(TestUsize(0)).id();
+
+    world.set_value(1);
+    world.entity_mut(entity).increase(3);
+    world.undo();
+
+    let history = world.resource::<UndoHistory>();
+    assert_eq!(history.undo_label(), Some("Set the value"));
+    assert_eq!(history.redo_label(), Some("Increase an entity"));
+    assert_eq!(history.undo_labels().collect::<Vec<_>>(), ["Set the value"]);
+}
+
+#[test]
+fn entity_commands() {
+    let mut world = World::new();
+    world.init_resource::<UndoHistory>();
+    let entity = world.spawn(TestUsize(0)).id();
+
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &world);
+    commands.entity(entity).increase(3);
+    commands.entity(entity).increase(4);
+    commands.undo();
+    queue.apply(&mut world);
+
+    assert_eq!(**world.get::<TestUsize>(entity).unwrap(), 3);
+    world.redo();
+    assert_eq!(**world.get::<TestUsize>(entity).unwrap(), 7);
+}
+
+#[test]
+fn groups() {
+    let mut world = World::new();
+    world.insert_resource(TestUsize(0));
+    world.init_resource::<UndoHistory>();
+
+    world.begin_undo_group("Set many");
+    world.set_value(1);
+    world.set_value(2);
+    world.set_value(3);
+    world.end_undo_group();
+    assert_eq!(
+        world.resource::<UndoHistory>().undo_label(),
+        Some("Set many")
+    );
+
+    world.undo();
+    assert_eq!(value(&world), 0);
+    world.redo();
+    assert_eq!(value(&world), 3);
+    assert_eq!(
+        world.resource::<UndoHistory>().undo_label(),
+        Some("Set many")
+    );
+
+    // nested undoable commands are reverted by their outer command
+    world.set_value_twice(4);
+    assert_eq!(value(&world), 8);
+    world.undo();
+    assert_eq!(value(&world), 3);
+}
+
+#[test]
+fn limit() {
+    let mut world = World::new();
+    world.insert_resource(TestUsize(0));
+    world.insert_resource(UndoHistory::with_limit(2));
+
+    for n in 1..=5 {
+        world.set_value(n);
+    }
+    world.undo();
+    world.undo();
+    world.undo();
+    assert_eq!(value(&world), 3);
+}
+
+/// A panic while undoing should not stop later commands from being recorded
+#[test]
+fn panic_while_undoing() {
+    let mut world = World::new();
+    world.insert_resource(TestUsize(0));
+    world.init_resource::<UndoHistory>();
+
+    world.set_fragile(1);
+    let result = catch_unwind(AssertUnwindSafe(|| world.undo()));
+    assert!(result.is_err());
+    assert!(!world.resource::<UndoHistory>().can_undo());
+
+    world.set_value(2);
+    assert!(world.resource::<UndoHistory>().can_undo());
+    world.undo();
+    assert_eq!(value(&world), 1);
+}
@@ -0,0 +1,138 @@
+#![cfg(feature = "serde")]
+
+use bevy::prelude::*;
+use bevy_commandify::json::Value;
+use bevy_commandify::*;
+use serde_json::json;
+
+mod common;
+use common::TestUsize;
+
+#[command(serde)]
+fn heal(world: &mut World, amount: usize) {
+    **world.resource_mut::<TestUsize>() += amount;
+}
+
+/// v1 was `{ "hp": n }`, v2 was `{ "amount": n }`
+#[command(serde, id = 42, migrate_from(v1 = rename_hp, v2 = add_multiplier))]
+fn damage(world: &mut World, amount: usize, multiplier: usize) {
+    **world.resource_mut::<TestUsize>() -= amount * multiplier;
+}
+
+/// Clashes with `damage`
+#[command(serde, id = 42)]
+fn destroy(_world: &mut World) {}
+
+fn rename_hp(mut args: Value) -> Value {
+    let hp = args["hp"].take();
+    json!({ "amount": hp })
+}
+
+fn add_multiplier(mut args: Value) -> Value {
+    args["multiplier"] = json!(1);
+    args
+}
+
+fn world() -> World {
+    let mut world = World::new();
+    world.insert_resource(TestUsize(100));
+    world.register_command::<HealCommand>();
+    world.register_command::<DamageCommand>();
+    world
+}
+
+fn request(command: &str, id: Option<u64>, version: Option<u32>, args: Value) -> CommandRequest {
+    CommandRequest {
+        command: command.into(),
+        entity: None,
+        args,
+        id,
+        version,
+    }
+}
+
+#[test]
+fn ids_and_versions() {
+    // ids are a stable hash of the name by default
+    assert_eq!(HealCommand::INFO.id, 4251589730245019);
+    assert_eq!(HealCommand::INFO.version, 1);
+    assert_eq!(DamageCommand::INFO.id, 42);
+    // versioned after the newest migration
+    assert_eq!(DamageCommand::INFO.version, 3);
+
+    let request = CommandRequest::new(&HealCommand { amount: 1 }, None).unwrap();
+    assert_eq!(request.id, Some(4251589730245019));
+    assert_eq!(request.version, Some(1));
+
+    let world = world();
+    let registry = world.resource::<CommandRegistry>();
+    assert_eq!(registry.get_by_id(42).unwrap().info().name, "damage");
+}
+
+/// Older payloads should be upgraded through each migration in turn
+#[test]
+fn migrate() {
+    let mut world = world();
+
+    world
+        .run_json_command(request("damage", None, Some(1), json!({ "hp": 5 })))
+        .unwrap();
+    assert_eq!(**world.resource::<TestUsize>(), 95);
+
+    world
+        .run_json_command(request("damage", None, Some(2), json!({ "amount": 5 })))
+        .unwrap();
+    assert_eq!(**world.resource::<TestUsize>(), 90);
+
+    world
+        .run_json_command(request(
+            "damage",
+            None,
+            Some(3),
+            json!({ "amount": 5, "multiplier": 2 }),
+        ))
+        .unwrap();
+    assert_eq!(**world.resource::<TestUsize>(), 80);
+}
+
+#[test]
+fn unsupported_versions() {
+    let mut world = world();
+
+    let result = world.run_json_command(request("damage", None, Some(4), json!({})));
+    assert!(matches!(
+        result,
+        Err(JsonError::UnsupportedVersion("damage", 4))
+    ));
+
+    let result = world.run_json_command(request("heal", None, Some(0), json!({ "amount": 1 })));
+    assert!(matches!(
+        result,
+        Err(JsonError::UnsupportedVersion("heal", 0))
+    ));
+    assert_eq!(**world.resource::<TestUsize>(), 100);
+}
+
+/// Ids should still find commands which have since been renamed
+#[test]
+fn find_by_id() {
+    let mut world = world();
+
+    world
+        .run_json_command(request(
+            "hurt",
+            Some(42),
+            None,
+            json!({ "amount": 1, "multiplier": 1 }),
+        ))
+        .unwrap();
+    assert_eq!(**world.resource::<TestUsize>(), 99);
+}
+
+/// Different commands may not share an id, or requests could reach the wrong command
+#[test]
+#[should_panic(expected = "commands `damage` and `destroy` have the same id 42")]
+fn duplicate_ids() {
+    let mut world = world();
+    world.register_command::<DestroyCommand>();
+}